@@ -0,0 +1,9 @@
+-- Add migration script here
+ALTER TABLE config ADD COLUMN signups_autoclose bool NOT NULL DEFAULT false;
+
+CREATE TABLE signup_schedule (
+    guild_id bigint NOT NULL,
+    action text NOT NULL,
+    run_at timestamptz NOT NULL,
+    CONSTRAINT unq_guild_action UNIQUE(guild_id, action)
+);
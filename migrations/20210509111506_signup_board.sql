@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE config ADD COLUMN signup_board_id bigint;
@@ -0,0 +1,14 @@
+-- Add migration script here
+ALTER TABLE config ADD COLUMN min_account_age int NOT NULL DEFAULT 0;
+ALTER TABLE config ADD COLUMN min_member_age int NOT NULL DEFAULT 0;
+ALTER TABLE config ADD COLUMN signup_role_id bigint;
+
+CREATE TABLE game_bans (
+  guild_id bigint NOT NULL,
+  user_id bigint NOT NULL,
+  reason text,
+  banned_by bigint NOT NULL,
+  banned_at timestamptz NOT NULL DEFAULT now(),
+  expires_at timestamptz,
+  CONSTRAINT unq_guild_user_ban UNIQUE(guild_id, user_id)
+);
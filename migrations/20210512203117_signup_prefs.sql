@@ -0,0 +1,8 @@
+-- Add migration script here
+CREATE TABLE signup_prefs (
+  guild_id bigint NOT NULL,
+  user_id bigint NOT NULL,
+  prefer text [] NOT NULL DEFAULT '{}',
+  avoid text [] NOT NULL DEFAULT '{}',
+  CONSTRAINT unq_guild_user_prefs UNIQUE(guild_id, user_id)
+);
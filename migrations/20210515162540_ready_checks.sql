@@ -0,0 +1,12 @@
+-- Add migration script here
+CREATE TABLE ready_checks (
+  guild_id bigint NOT NULL PRIMARY KEY,
+  channel_id bigint NOT NULL,
+  message_id bigint NOT NULL,
+  results_channel_id bigint NOT NULL,
+  ends_at timestamptz NOT NULL,
+  players bigint [] NOT NULL,
+  confirmed bigint [] NOT NULL DEFAULT '{}',
+  no_shows bigint [],
+  ended bool NOT NULL DEFAULT false
+);
@@ -0,0 +1,14 @@
+-- Add migration script here
+ALTER TABLE config ADD COLUMN whispers_on bool NOT NULL DEFAULT false;
+ALTER TABLE config ADD COLUMN whispers_public bool NOT NULL DEFAULT false;
+ALTER TABLE config ADD COLUMN whisper_limit int NOT NULL DEFAULT 0;
+ALTER TABLE config ADD COLUMN whisper_log_id bigint;
+
+CREATE TABLE whispers (
+  guild_id bigint NOT NULL,
+  cycle smallint NOT NULL,
+  sender_id bigint NOT NULL,
+  target_id bigint NOT NULL,
+  content text NOT NULL,
+  sent_at timestamptz NOT NULL DEFAULT now()
+);
@@ -0,0 +1,12 @@
+-- Add migration script here
+ALTER TABLE config ADD COLUMN anonymous bool NOT NULL DEFAULT false;
+ALTER TABLE config ADD COLUMN anon_prefix text NOT NULL DEFAULT '!';
+
+CREATE TABLE aliases (
+  guild_id bigint NOT NULL,
+  user_id bigint NOT NULL,
+  alias text NOT NULL,
+  avatar_url text,
+  CONSTRAINT unq_guild_alias_user UNIQUE(guild_id, user_id),
+  CONSTRAINT unq_guild_alias UNIQUE(guild_id, alias)
+);
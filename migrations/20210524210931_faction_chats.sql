@@ -0,0 +1,10 @@
+-- Add migration script here
+ALTER TABLE config ADD COLUMN game_category_id bigint;
+
+CREATE TABLE faction_chats (
+  guild_id bigint NOT NULL,
+  name text NOT NULL,
+  channel_id bigint NOT NULL,
+  created_at timestamptz NOT NULL DEFAULT now(),
+  CONSTRAINT unq_guild_faction UNIQUE(guild_id, channel_id)
+);
@@ -0,0 +1,13 @@
+-- Add migration script here
+ALTER TABLE config ADD COLUMN dead_chat_id bigint;
+ALTER TABLE config ADD COLUMN dead_see_chats bool NOT NULL DEFAULT false;
+
+-- Overwrites a dead player had before being given a view of a channel.
+CREATE TABLE dead_views (
+  guild_id bigint NOT NULL,
+  user_id bigint NOT NULL,
+  channel_id bigint NOT NULL,
+  previous_allow bigint,
+  previous_deny bigint,
+  CONSTRAINT unq_guild_user_channel UNIQUE(guild_id, user_id, channel_id)
+);
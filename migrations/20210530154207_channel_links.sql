@@ -0,0 +1,11 @@
+-- Add migration script here
+ALTER TABLE config ADD COLUMN anonymous_links bool NOT NULL DEFAULT true;
+
+CREATE TABLE channel_links (
+  guild_id bigint NOT NULL,
+  first_channel_id bigint NOT NULL,
+  second_channel_id bigint NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT now(),
+  CONSTRAINT unq_guild_first_channel UNIQUE(guild_id, first_channel_id),
+  CONSTRAINT unq_guild_second_channel UNIQUE(guild_id, second_channel_id)
+);
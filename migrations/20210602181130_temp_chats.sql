@@ -0,0 +1,13 @@
+-- Add migration script here
+ALTER TABLE channel_links ADD COLUMN first_name text;
+ALTER TABLE channel_links ADD COLUMN until_cycle bool NOT NULL DEFAULT false;
+
+CREATE TABLE temp_chats (
+  guild_id bigint NOT NULL,
+  name text NOT NULL,
+  channel_id bigint NOT NULL,
+  until_phase_end bool NOT NULL DEFAULT false,
+  archived bool NOT NULL DEFAULT false,
+  created_at timestamp with time zone NOT NULL DEFAULT now(),
+  CONSTRAINT unq_guild_temp_chat UNIQUE(guild_id, channel_id)
+);
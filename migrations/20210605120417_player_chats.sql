@@ -0,0 +1,11 @@
+-- Add migration script here
+ALTER TABLE config ADD COLUMN pc_category_id bigint;
+
+CREATE TABLE player_chats (
+  guild_id bigint NOT NULL,
+  user_id bigint NOT NULL,
+  channel_id bigint NOT NULL,
+  archived bool NOT NULL DEFAULT false,
+  created_at timestamp with time zone NOT NULL DEFAULT now(),
+  CONSTRAINT unq_guild_player_chat UNIQUE(guild_id, user_id)
+);
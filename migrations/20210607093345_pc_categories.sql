@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE config ADD COLUMN pc_category_ids bigint[] NOT NULL DEFAULT '{}';
+UPDATE config SET pc_category_ids = ARRAY[pc_category_id] WHERE pc_category_id IS NOT NULL;
+ALTER TABLE config DROP COLUMN pc_category_id;
@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE config ADD COLUMN cycle_template jsonb;
@@ -0,0 +1,19 @@
+-- Add migration script here
+ALTER TABLE logging ADD COLUMN archive_on bool NOT NULL DEFAULT false;
+ALTER TABLE logging ADD COLUMN archive_retention int NOT NULL DEFAULT 30;
+
+CREATE TABLE message_archive (
+  message_id bigint NOT NULL PRIMARY KEY,
+  guild_id bigint NOT NULL,
+  channel_id bigint NOT NULL,
+  author_id bigint NOT NULL,
+  author_tag text NOT NULL,
+  author_avatar text NOT NULL,
+  content text NOT NULL,
+  attachments jsonb NOT NULL DEFAULT '[]',
+  created_at timestamp with time zone NOT NULL,
+  edited_at timestamp with time zone,
+  deleted_at timestamp with time zone
+);
+
+CREATE INDEX idx_message_archive_guild_created ON message_archive(guild_id, created_at);
@@ -0,0 +1,15 @@
+-- Add migration script here
+ALTER TABLE logging ADD COLUMN attachments_on bool NOT NULL DEFAULT false;
+ALTER TABLE logging ADD COLUMN attachment_max_size int NOT NULL DEFAULT 8;
+ALTER TABLE logging ADD COLUMN attachment_retention int NOT NULL DEFAULT 7;
+
+CREATE TABLE stored_attachments (
+  attachment_id bigint NOT NULL PRIMARY KEY,
+  message_id bigint NOT NULL,
+  guild_id bigint NOT NULL,
+  filename text NOT NULL,
+  size bigint NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_stored_attachments_message ON stored_attachments(message_id);
@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE logging ADD COLUMN disabled_events text[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,7 @@
+-- Add migration script here
+CREATE TABLE log_routes (
+  guild_id bigint NOT NULL,
+  event text NOT NULL,
+  channel_id bigint NOT NULL,
+  PRIMARY KEY (guild_id, event)
+);
@@ -0,0 +1,2 @@
+-- Add migration script here
+ALTER TABLE logging ADD COLUMN ghost_pings_in_channel bool NOT NULL DEFAULT false;
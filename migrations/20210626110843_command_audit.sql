@@ -0,0 +1,18 @@
+-- Add migration script here
+CREATE TABLE command_audit (
+  id serial PRIMARY KEY,
+  guild_id bigint NOT NULL,
+  user_id bigint NOT NULL,
+  user_tag text NOT NULL,
+  command text NOT NULL,
+  args text NOT NULL,
+  success bool NOT NULL,
+  error text,
+  created_at timestamp with time zone NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_command_audit_guild_created ON command_audit(guild_id, created_at);
+
+-- Mirroring the audit to the log channel is opt-in.
+ALTER TABLE logging ALTER COLUMN disabled_events SET DEFAULT '{audit}';
+UPDATE logging SET disabled_events = array_append(disabled_events, 'audit');
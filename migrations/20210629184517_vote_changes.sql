@@ -0,0 +1,15 @@
+-- Add migration script here
+CREATE TABLE vote_changes (
+  id serial PRIMARY KEY,
+  guild_id bigint NOT NULL,
+  channel_id bigint NOT NULL,
+  message_id bigint NOT NULL,
+  user_id bigint NOT NULL,
+  user_tag text NOT NULL,
+  old_vote text NOT NULL,
+  new_vote text NOT NULL,
+  deleted bool NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_vote_changes_channel ON vote_changes(channel_id);
@@ -0,0 +1,7 @@
+-- Add migration script here
+CREATE TABLE replacement_signups (
+  guild_id bigint NOT NULL,
+  user_id bigint NOT NULL,
+  created_at timestamp with time zone NOT NULL DEFAULT now(),
+  PRIMARY KEY (guild_id, user_id)
+);
@@ -0,0 +1,7 @@
+-- Add migration script here
+-- Guilds that already log messages only get edits and deletes, as before.
+-- The new events are opt-in for them.
+UPDATE logging SET disabled_events = disabled_events || ARRAY(
+    SELECT e FROM unnest(ARRAY['roles', 'nicknames', 'joins', 'overwrites', 'ghostpings', 'votes']) AS e
+    WHERE e <> ALL(disabled_events)
+);
@@ -0,0 +1,352 @@
+// This module keeps a copy of messages sent in logged channels.
+//
+// The cache only holds a few messages per channel, so without the archive
+// most deleted or edited messages can't be logged with their content. The
+// archive is optional and old messages are removed after the retention period.
+//
+// Attachments can be downloaded too, so they can be uploaded again when their
+// message is deleted. They are kept in the storage set up in the environment.
+
+use crate::{
+    events::{is_allowed_channel, LogEvent, LogSettings},
+    utils::storage::Storage,
+    ConnectionPool, StorageContainer,
+};
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serenity::{
+    http::AttachmentType,
+    model::prelude::{Attachment, GuildId, Message, MessageId},
+    prelude::Context,
+};
+use sqlx::{types::Json, PgPool};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time::timeout;
+use tracing::{error, instrument};
+
+/// Longest time to wait for the attachments of a deleted message to be saved.
+const DOWNLOAD_WAIT: Duration = Duration::from_secs(60);
+
+/// Messages whose attachments are being saved. Their lock is held until all
+/// of them are.
+static DOWNLOADS: Lazy<Mutex<HashMap<MessageId, Arc<tokio::sync::Mutex<()>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Details of an attachment of an archived message.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub(crate) struct ArchivedAttachment {
+    pub filename: String,
+    pub url: String,
+    pub size: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct ArchivedMessage {
+    pub author_id: i64,
+    pub author_tag: String,
+    pub author_avatar: String,
+    pub content: String,
+    pub attachments: Json<Vec<ArchivedAttachment>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A downloaded attachment of a deleted message.
+pub(crate) struct StoredFile {
+    pub filename: String,
+    pub data: Vec<u8>,
+}
+
+impl StoredFile {
+    pub(crate) fn to_attachment(&self) -> AttachmentType<'_> {
+        AttachmentType::Bytes {
+            data: Cow::from(&self.data[..]),
+            filename: self.filename.clone(),
+        }
+    }
+}
+
+/// Saves a message if it was sent in a logged channel and the archive is on.
+///
+/// Its attachments are downloaded too if that is enabled. Messages in the
+/// voting channel of the current cycle are saved while vote changes are
+/// logged, even if the archive is off.
+#[instrument(skip(ctx, msg, settings))]
+pub(crate) async fn archive_message(
+    ctx: &Context,
+    msg: &Message,
+    settings: Option<&LogSettings>,
+    votes_channel_id: Option<i64>,
+) {
+    if msg.author.bot {
+        return;
+    }
+
+    let guild_id = match msg.guild_id {
+        Some(i) => i,
+        None => return,
+    };
+
+    let settings = match settings {
+        Some(s) => s,
+        None => return,
+    };
+
+    let is_votes =
+        votes_channel_id == Some(msg.channel_id.0 as i64) && settings.logs(LogEvent::Votes);
+    if !settings.archive_on && !settings.attachments_on && !is_votes {
+        return;
+    }
+
+    let channel = match ctx.cache.guild_channel(msg.channel_id).await {
+        Some(c) => c,
+        None => return,
+    };
+
+    let allowed = is_allowed_channel(ctx, &channel, settings).await;
+    if !allowed && !is_votes {
+        return;
+    }
+
+    // Downloads can take a while, so they don't hold up other message handlers.
+    let max_size = settings.attachment_max_size as u64 * 1024 * 1024;
+    let attachments: Vec<Attachment> = msg
+        .attachments
+        .iter()
+        .filter(|a| a.size <= max_size)
+        .cloned()
+        .collect();
+    if allowed && settings.attachments_on && !attachments.is_empty() {
+        let ctx = ctx.clone();
+        let message_id = msg.id;
+        let lock = Arc::new(tokio::sync::Mutex::new(()));
+        let guard = Arc::clone(&lock).lock_owned().await;
+        DOWNLOADS.lock().unwrap().insert(message_id, lock);
+
+        tokio::spawn(async move {
+            {
+                let data_read = ctx.data.read().await;
+                let pool = data_read.get::<ConnectionPool>().unwrap();
+                let storage = data_read.get::<StorageContainer>().unwrap();
+                for attachment in &attachments {
+                    store_attachment(pool, storage, guild_id, message_id, attachment).await;
+                }
+            }
+
+            DOWNLOADS.lock().unwrap().remove(&message_id);
+            drop(guard);
+        });
+    }
+
+    let archived = is_votes || (allowed && settings.archive_on);
+    if !archived {
+        return;
+    }
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let attachments: Vec<ArchivedAttachment> = msg
+        .attachments
+        .iter()
+        .map(|a| ArchivedAttachment {
+            filename: a.filename.clone(),
+            url: a.url.clone(),
+            size: a.size,
+        })
+        .collect();
+
+    let res = sqlx::query!(
+        "
+        INSERT INTO message_archive(
+            guild_id, channel_id, message_id, author_id, author_tag,
+            author_avatar, content, attachments, created_at
+        ) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        ON CONFLICT (message_id) DO NOTHING;
+        ",
+        guild_id.0 as i64,
+        msg.channel_id.0 as i64,
+        msg.id.0 as i64,
+        msg.author.id.0 as i64,
+        msg.author.tag(),
+        msg.author.face(),
+        msg.content,
+        serde_json::to_value(&attachments).unwrap_or_default(),
+        msg.timestamp
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(why) = res {
+        error!("Unable to archive message: {}", why);
+    }
+}
+
+/// Downloads an attachment and saves it in the storage.
+async fn store_attachment(
+    pool: &PgPool,
+    storage: &Storage,
+    guild_id: GuildId,
+    message_id: MessageId,
+    attachment: &Attachment,
+) {
+    let data = match attachment.download().await {
+        Ok(d) => d,
+        Err(why) => {
+            error!("Unable to download attachment: {}", why);
+            return;
+        }
+    };
+
+    let key = attachment_key(
+        guild_id.0 as i64,
+        message_id.0 as i64,
+        *attachment.id.as_u64() as i64,
+    );
+    if let Err(why) = storage.put(&key, data).await {
+        error!("Unable to store attachment: {}", why);
+        return;
+    }
+
+    let res = sqlx::query!(
+        "
+        INSERT INTO stored_attachments(attachment_id, message_id, guild_id, filename, size)
+        VALUES($1, $2, $3, $4, $5) ON CONFLICT (attachment_id) DO NOTHING;
+        ",
+        *attachment.id.as_u64() as i64,
+        message_id.0 as i64,
+        guild_id.0 as i64,
+        attachment.filename,
+        attachment.size as i64
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(why) = res {
+        error!("Unable to save stored attachment details: {}", why);
+    }
+}
+
+/// Gets the stored attachments of a message, with the ID of its guild.
+///
+/// Messages are often deleted right after they are sent, so attachments that
+/// are still being saved are waited for.
+pub(crate) async fn get_stored_files(
+    pool: &PgPool,
+    storage: &Storage,
+    message_id: MessageId,
+) -> Option<(GuildId, Vec<StoredFile>)> {
+    let pending = DOWNLOADS.lock().unwrap().get(&message_id).cloned();
+    if let Some(lock) = pending {
+        let _ = timeout(DOWNLOAD_WAIT, lock.lock()).await;
+    }
+
+    let rows = sqlx::query!(
+        "
+        SELECT attachment_id, guild_id, filename FROM stored_attachments
+        WHERE message_id = $1 ORDER BY attachment_id;
+        ",
+        message_id.0 as i64
+    )
+    .fetch_all(pool)
+    .await
+    .ok()?;
+
+    let guild_id = GuildId(rows.first()?.guild_id as u64);
+
+    let mut files = Vec::new();
+    for row in rows {
+        let key = attachment_key(row.guild_id, message_id.0 as i64, row.attachment_id);
+        match storage.get(&key).await {
+            Ok(data) => files.push(StoredFile {
+                filename: row.filename,
+                data,
+            }),
+            Err(why) => error!("Unable to get stored attachment: {}", why),
+        }
+    }
+
+    Some((guild_id, files))
+}
+
+/// Key under which an attachment is saved in the storage.
+pub(crate) fn attachment_key(guild_id: i64, message_id: i64, attachment_id: i64) -> String {
+    format!("{}/{}/{}", guild_id, message_id, attachment_id)
+}
+
+/// Gets an archived message.
+pub(crate) async fn get_archived_message(
+    pool: &PgPool,
+    message_id: MessageId,
+) -> Option<ArchivedMessage> {
+    sqlx::query_as_unchecked!(
+        ArchivedMessage,
+        "
+        SELECT author_id, author_tag, author_avatar,
+        content, attachments, created_at
+        FROM message_archive WHERE message_id = $1;
+        ",
+        message_id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten()
+}
+
+/// Gets all archived messages out of `message_ids`.
+pub(crate) async fn get_archived_messages(
+    pool: &PgPool,
+    message_ids: &[MessageId],
+) -> Vec<ArchivedMessage> {
+    let ids: Vec<i64> = message_ids.iter().map(|i| i.0 as i64).collect();
+
+    sqlx::query_as_unchecked!(
+        ArchivedMessage,
+        "
+        SELECT author_id, author_tag, author_avatar,
+        content, attachments, created_at
+        FROM message_archive WHERE message_id = ANY($1);
+        ",
+        &ids
+    )
+    .fetch_all(pool)
+    .await
+    .unwrap_or_default()
+}
+
+/// Saves the new content of an edited message.
+pub(crate) async fn update_archived_message(pool: &PgPool, message_id: MessageId, content: &str) {
+    let res = sqlx::query!(
+        "UPDATE message_archive SET content = $2, edited_at = now() WHERE message_id = $1;",
+        message_id.0 as i64,
+        content
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(why) = res {
+        error!("Unable to update archived message: {}", why);
+    }
+}
+
+/// Marks archived messages as deleted.
+pub(crate) async fn mark_archived_deleted(pool: &PgPool, message_ids: &[MessageId]) {
+    let ids: Vec<i64> = message_ids.iter().map(|i| i.0 as i64).collect();
+
+    let res = sqlx::query!(
+        "UPDATE message_archive SET deleted_at = now() WHERE message_id = ANY($1);",
+        &ids
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(why) = res {
+        error!("Unable to mark archived messages as deleted: {}", why);
+    }
+}
@@ -0,0 +1,207 @@
+// This module keeps an audit log of host commands.
+//
+// Commands of the Host Utility and TvM Settings groups are recorded after they
+// run, with who ran them, their arguments and whether they returned an error.
+// Hosts can browse the log with the `audit` command, and it can be mirrored to
+// the log channel with `log event audit true`.
+//
+// Most commands answer bad input, like a member that can't be found, with a
+// message instead of an error, so those uses are recorded as successful. Only
+// errors returned by a command, which the bot reports in the channel, count as
+// failures.
+
+use crate::{
+    commands::{host::UTILITIES_GROUP, setup::TVMSET_GROUP},
+    dynamic_prefix,
+    events::{get_event_log, LogEvent},
+    ConnectionPool,
+};
+use chrono::Utc;
+use serenity::{
+    framework::standard::{CommandGroup, CommandResult},
+    model::prelude::Message,
+    prelude::Context,
+};
+use tracing::{error, instrument};
+
+/// Finds the full name and arguments of an audited command used in a message.
+fn audited_invocation(text: &str, cmd_name: &str) -> Option<(String, String)> {
+    let (first, rest) = split_word(text);
+
+    if is_tvmset_prefix(first) {
+        // Without a command name, the default command of the group is used.
+        let (second, args) = split_word(rest);
+        let args = match find_command(&TVMSET_GROUP, second) {
+            Some(_) => args,
+            None => rest,
+        };
+
+        return Some((format!("tvm {}", cmd_name), args.to_string()));
+    }
+
+    // Browsing the audit log isn't worth recording.
+    find_command(&UTILITIES_GROUP, first)
+        .filter(|n| *n != "audit")
+        .map(|_| (cmd_name.to_string(), rest.to_string()))
+}
+
+fn is_tvmset_prefix(word: &str) -> bool {
+    TVMSET_GROUP
+        .options
+        .prefixes
+        .iter()
+        .any(|p| p.eq_ignore_ascii_case(word))
+}
+
+/// Finds a command of a group by any of its names, and returns its main name.
+fn find_command(group: &CommandGroup, name: &str) -> Option<&'static str> {
+    group
+        .options
+        .commands
+        .iter()
+        .find(|c| c.options.names.iter().any(|n| n.eq_ignore_ascii_case(name)))
+        .map(|c| c.options.names[0])
+}
+
+/// Strips a prefix from a message the way the framework does, ignoring case.
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let start = text.get(..prefix.len())?;
+    if start.to_lowercase() == prefix.to_lowercase() {
+        Some(&text[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+fn split_word(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    match text.find(char::is_whitespace) {
+        Some(i) => (&text[..i], text[i..].trim()),
+        None => (text, ""),
+    }
+}
+
+/// Gets the name an audited command is recorded with, from any of its names.
+///
+/// Commands of the TvM Settings group start with `tvm`, like `tvm signopen`.
+pub(crate) fn audited_command_name(name: &str) -> Option<String> {
+    let (first, rest) = split_word(name);
+
+    if is_tvmset_prefix(first) {
+        find_command(&TVMSET_GROUP, rest).map(|n| format!("tvm {}", n))
+    } else if rest.is_empty() {
+        find_command(&UTILITIES_GROUP, first)
+            .filter(|n| *n != "audit")
+            .map(|n| n.to_string())
+    } else {
+        None
+    }
+}
+
+/// Records a command if it belongs to an audited group.
+#[instrument(skip(ctx, msg, result))]
+pub(crate) async fn record_command(
+    ctx: &Context,
+    msg: &Message,
+    cmd_name: &str,
+    result: &CommandResult,
+) {
+    let guild_id = match msg.guild_id {
+        Some(i) => i,
+        None => return,
+    };
+
+    let prefix = dynamic_prefix(ctx, &msg.guild_id)
+        .await
+        .unwrap_or_else(|| "-".to_string());
+    let bot_id = ctx.cache.current_user_id().await.0;
+
+    let text = match strip_prefix_ignore_case(&msg.content, &prefix) {
+        Some(t) => t,
+        None => {
+            let mentions = [format!("<@{}>", bot_id), format!("<@!{}>", bot_id)];
+            match mentions
+                .iter()
+                .find_map(|m| msg.content.strip_prefix(m.as_str()))
+            {
+                Some(t) => t,
+                None => return,
+            }
+        }
+    };
+
+    let (command, args) = match audited_invocation(text, cmd_name) {
+        Some(i) => i,
+        None => return,
+    };
+
+    let error = result.as_ref().err().map(|e| e.to_string());
+
+    {
+        let data_read = ctx.data.read().await;
+        let pool = data_read.get::<ConnectionPool>().unwrap();
+
+        let res = sqlx::query!(
+            "
+            INSERT INTO command_audit(guild_id, user_id, user_tag, command, args, success, error)
+            VALUES($1, $2, $3, $4, $5, $6, $7);
+            ",
+            guild_id.0 as i64,
+            msg.author.id.0 as i64,
+            msg.author.tag(),
+            command,
+            args,
+            error.is_none(),
+            error
+        )
+        .execute(pool)
+        .await;
+
+        if let Err(why) = res {
+            error!("Unable to record command in the audit log: {}", why);
+        }
+    }
+
+    let (_, log_channel) = match get_event_log(ctx, guild_id, LogEvent::Audit).await {
+        Some(l) => l,
+        None => return,
+    };
+
+    let mut used = format!("{}{} {}", prefix, command, args);
+    if let Some((end, _)) = used.char_indices().nth(1000) {
+        used.truncate(end);
+    }
+
+    let sent = log_channel
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(if error.is_none() { 0x00CDFF } else { 0xFF0000 });
+                e.description(format!("```{}```", used.trim().replace('`', "'")));
+                e.timestamp(Utc::now().to_rfc3339());
+                e.field("Channel", format!("<#{}>", msg.channel_id.0), true);
+                if let Some(why) = &error {
+                    e.field("Error", why, true);
+                }
+                e.footer(|f| {
+                    f.text(format!("User ID: {}", msg.author.id.0));
+
+                    f
+                });
+                e.author(|a| {
+                    a.name(format!("{} - Command Used", msg.author.tag()));
+                    a.icon_url(msg.author.face());
+
+                    a
+                });
+
+                e
+            });
+
+            m
+        })
+        .await;
+
+    if let Err(why) = sent {
+        error!("Failed to mirror command to the log channel: {}", why);
+    }
+}
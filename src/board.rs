@@ -0,0 +1,289 @@
+// This module keeps the sign-up board up to date.
+//
+// The sign-up board is a pinned embed in the sign-ups channel. It lists
+// players, spectators, replacements and open slots, and is edited every
+// time sign-ups change. If the message is deleted, a new one is posted.
+
+use crate::{utils::constants::EMBED_COLOUR, ConnectionPool};
+use once_cell::sync::Lazy;
+use serenity::{
+    model::{
+        misc::Mentionable,
+        prelude::{ChannelId, GuildId, Member, MessageId, RoleId},
+    },
+    prelude::Context,
+    Error,
+};
+use serenity_utils::prelude::EmbedBuilder;
+use std::{collections::HashMap, fmt::Write, sync::Arc};
+use tokio::sync::Mutex;
+use tracing::{error, instrument};
+
+// Leave some room below Discord's limits for the "and x more" text.
+const MAX_DESCRIPTION_LEN: usize = 1900;
+const MAX_FIELD_LEN: usize = 950;
+
+// A command and the role change it causes both update the board. Without
+// these, both could post a new board at the same time.
+static BOARD_LOCKS: Lazy<Mutex<HashMap<GuildId, Arc<Mutex<()>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[instrument(skip(ctx))]
+pub(crate) async fn update_signup_board(ctx: &Context, guild_id: GuildId) {
+    let lock = BOARD_LOCKS
+        .lock()
+        .await
+        .entry(guild_id)
+        .or_default()
+        .clone();
+    let _guard = lock.lock().await;
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let settings = match sqlx::query!(
+        "
+        SELECT
+            signups_channel_id,
+            signup_board_id,
+            player_role_id,
+            spec_role_id,
+            repl_role_id,
+            total_players,
+            signups_on,
+            players
+        FROM config WHERE guild_id = $1;
+        ",
+        guild_id.0 as i64
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(s) => s,
+        Err(why) => {
+            error!("Unable to fetch sign-up board settings: {}", why);
+            return;
+        }
+    };
+
+    // There is no board without a sign-ups channel.
+    let channel_id = match settings.signups_channel_id {
+        Some(i) => ChannelId(i as u64),
+        None => return,
+    };
+
+    let members = match ctx.cache.guild_field(guild_id, |g| g.members.clone()).await {
+        Some(m) => m,
+        None => return,
+    };
+
+    let with_role = |role_id: Option<i64>| -> Vec<&Member> {
+        match role_id {
+            Some(i) => members
+                .values()
+                .filter(|m| m.roles.contains(&RoleId(i as u64)))
+                .collect(),
+            None => Vec::new(),
+        }
+    };
+
+    let signups_open = settings.signups_on.unwrap_or(true);
+
+    // Players are listed in the order they signed up in.
+    let order = settings.players.unwrap_or_default();
+    let mut players = with_role(settings.player_role_id);
+    players.sort_by_key(|m| {
+        order
+            .iter()
+            .position(|i| *i == m.user.id.0 as i64)
+            .unwrap_or(usize::MAX)
+    });
+
+    let total_players = settings.total_players.unwrap_or(12).max(0) as usize;
+
+    let mut lines = players
+        .iter()
+        .map(|m| m.mention().to_string())
+        .collect::<Vec<_>>();
+    while lines.len() < total_players {
+        lines.push(String::from("*Open slot*"));
+    }
+
+    let mut embed = EmbedBuilder::new();
+    embed
+        .set_title(format!("Sign-ups ({}/{})", players.len(), total_players))
+        .set_colour(EMBED_COLOUR)
+        .set_description(numbered_list(&lines, MAX_DESCRIPTION_LEN))
+        .add_field((
+            "Spectators",
+            mention_list(&with_role(settings.spec_role_id), MAX_FIELD_LEN),
+            false,
+        ))
+        .add_field((
+            "Replacements",
+            mention_list(&with_role(settings.repl_role_id), MAX_FIELD_LEN),
+            false,
+        ))
+        .set_footer_with(|f| {
+            f.set_text(if signups_open {
+                "Sign-ups are open."
+            } else {
+                "Sign-ups are closed."
+            })
+        });
+
+    if let Some(id) = settings.signup_board_id {
+        let res = channel_id
+            .edit_message(&ctx.http, id as u64, |m| {
+                m.embed(|e| {
+                    *e = embed.to_create_embed();
+                    e
+                })
+            })
+            .await;
+
+        match res {
+            Ok(_) => return,
+            // Only a deleted board is posted again. Other errors may not last.
+            Err(Error::Http(e)) if matches!(e.status_code(), Some(s) if s.as_u16() == 404) => (),
+            Err(why) => {
+                error!("Unable to edit the sign-up board: {}", why);
+                return;
+            }
+        }
+    }
+
+    // The board doesn't exist yet, or it was deleted. We'll post a new one.
+    let message = match channel_id
+        .send_message(&ctx.http, |m| m.set_embed(embed.to_create_embed()))
+        .await
+    {
+        Ok(m) => m,
+        Err(why) => {
+            error!("Unable to post the sign-up board: {}", why);
+            return;
+        }
+    };
+
+    // The board still works if it can't be pinned.
+    let _ = message.pin(&ctx.http).await;
+
+    if let Err(why) = sqlx::query!(
+        "UPDATE config SET signup_board_id = $2 WHERE guild_id = $1;",
+        guild_id.0 as i64,
+        message.id.0 as i64
+    )
+    .execute(pool)
+    .await
+    {
+        error!("Unable to save the sign-up board message: {}", why);
+    }
+}
+
+/// Updates the sign-up board if a member got or lost a sign-up role.
+#[instrument(skip(ctx))]
+pub(crate) async fn board_member_update(ctx: &Context, old: Option<&Member>, new: &Member) {
+    let changed: Vec<&RoleId> = match old {
+        Some(old) => old
+            .roles
+            .iter()
+            .filter(|r| !new.roles.contains(r))
+            .chain(new.roles.iter().filter(|r| !old.roles.contains(r)))
+            .collect(),
+        // Without the old member, only roles the member has now can be checked.
+        None => new.roles.iter().collect(),
+    };
+    if changed.is_empty() {
+        return;
+    }
+
+    let roles = {
+        let data_read = ctx.data.read().await;
+        let pool = data_read.get::<ConnectionPool>().unwrap();
+
+        match sqlx::query!(
+            "SELECT player_role_id, spec_role_id, repl_role_id FROM config WHERE guild_id = $1;",
+            new.guild_id.0 as i64
+        )
+        .fetch_optional(pool)
+        .await
+        {
+            Ok(Some(r)) => [r.player_role_id, r.spec_role_id, r.repl_role_id],
+            _ => return,
+        }
+    };
+
+    if changed.iter().any(|r| roles.contains(&Some(r.0 as i64))) {
+        update_signup_board(ctx, new.guild_id).await;
+    }
+}
+
+/// Posts the sign-up board again if the deleted message was the board.
+pub(crate) async fn board_delete_handler(
+    ctx: &Context,
+    guild_id: Option<GuildId>,
+    message_id: MessageId,
+) {
+    let guild_id = match guild_id {
+        Some(i) => i,
+        None => return,
+    };
+
+    let is_board = {
+        let data_read = ctx.data.read().await;
+        let pool = data_read.get::<ConnectionPool>().unwrap();
+
+        sqlx::query!(
+            "SELECT signup_board_id FROM config WHERE guild_id = $1;",
+            guild_id.0 as i64
+        )
+        .fetch_optional(pool)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|r| r.signup_board_id)
+            == Some(message_id.0 as i64)
+    };
+
+    if is_board {
+        update_signup_board(ctx, guild_id).await;
+    }
+}
+
+fn numbered_list(lines: &[String], max_len: usize) -> String {
+    let mut text = String::new();
+    for (idx, line) in lines.iter().enumerate() {
+        if text.len() + line.len() > max_len {
+            let _ = write!(text, "\n...and {} more", lines.len() - idx);
+            break;
+        }
+        let _ = write!(text, "\n{}. {}", idx + 1, line);
+    }
+
+    if text.is_empty() {
+        String::from("No players yet.")
+    } else {
+        text
+    }
+}
+
+fn mention_list(members: &[&Member], max_len: usize) -> String {
+    let mut text = String::new();
+    for (idx, member) in members.iter().enumerate() {
+        let mention = member.mention().to_string();
+        if text.len() + mention.len() > max_len {
+            let _ = write!(text, " and {} more", members.len() - idx);
+            break;
+        }
+        if !text.is_empty() {
+            text.push_str(", ");
+        }
+        text.push_str(&mention);
+    }
+
+    if text.is_empty() {
+        String::from("None")
+    } else {
+        text
+    }
+}
@@ -1,12 +1,17 @@
 //! All host utility commands and related functions are defined here.
 
 use crate::{
-    commands::setup::Cycle,
-    utils::{checks::*, constants::EMBED_COLOUR, converters::*},
+    audit::audited_command_name,
+    board::update_signup_board,
+    commands::{setup::Cycle, user::find_private_chat},
+    dynamic_prefix,
+    relay::end_links,
+    template::{fill, ChannelUse, CycleTemplate, GameRoles, Phase},
+    utils::{checks::*, constants::EMBED_COLOUR, converters::*, transaction::Transaction},
     ConnectionPool,
 };
 use chrono::offset::Utc;
-use rand::Rng;
+use rand::seq::SliceRandom;
 use serenity::{
     framework::standard::{
         macros::{command, group},
@@ -16,8 +21,11 @@ use serenity::{
     prelude::*,
 };
 use serenity_utils::{conversion::Conversion, prompt::yes_or_no_prompt};
-use sqlx::types::Json;
-use std::fmt::Write;
+use sqlx::{types::Json, PgPool};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
 
 pub(crate) struct Data {
     pub(crate) player_role_id: Option<i64>,
@@ -37,7 +45,12 @@ pub(crate) struct CycleContainer {
 /// Player role must be set to use this command. Additionally, the number of
 /// roles in the list must be exactly equal to number of members with the Player role.
 ///
-/// You can specify one role multiple times.
+/// You can specify one role multiple times. You can also add the faction of
+/// a role in brackets, like `doctor (town)`.
+///
+/// The roles are assigned so that the preferences players gave when signing
+/// up are met as much as possible. The preferences that couldn't be met are
+/// listed after the roles.
 ///
 /// **Example**
 ///
@@ -108,21 +121,52 @@ async fn randomize_roles(ctx: &Context, msg: &Message, args: Args) -> CommandRes
         return Ok(());
     }
 
-    // let mut assigned_roles = HashMap::new();
+    let prefs = get_preferences(pool, guild.id).await?;
+    let player_prefs: Vec<Option<&Preferences>> = players
+        .iter()
+        .map(|p| prefs.get(&(p.user.id.0 as i64)))
+        .collect();
+
+    args.shuffle(&mut rand::thread_rng());
+
+    // Swap roles between players as long as it meets more preferences.
+    // The total of unmet preferences goes down with every swap, so this ends.
+    let unmet = |i: usize, role: &str| match player_prefs[i] {
+        Some(p) => p.unmet(role).len(),
+        None => 0,
+    };
+    loop {
+        let mut improved = false;
+        for i in 0..args.len() {
+            for j in (i + 1)..args.len() {
+                let before = unmet(i, args[i]) + unmet(j, args[j]);
+                let after = unmet(i, args[j]) + unmet(j, args[i]);
+                if after < before {
+                    args.swap(i, j);
+                    improved = true;
+                }
+            }
+        }
+
+        if !improved {
+            break;
+        }
+    }
+
     let mut assigned_roles = String::new();
-    for player in players {
-        // We can unwrap here safely because lengths are equal.
-        let mut rng = rand::thread_rng();
-        let index = rng.gen_range(0..args.len());
+    let mut unmet_prefs = String::new();
+    for (i, player) in players.iter().enumerate() {
+        write!(assigned_roles, "\n{}: {}", player.display_name(), args[i])?;
 
-        write!(
-            assigned_roles,
-            "\n{}: {}",
-            player.display_name(),
-            args[index]
-        )?;
+        if let Some(p) = player_prefs[i] {
+            for pref in p.unmet(args[i]) {
+                write!(unmet_prefs, "\n{}: {}", player.display_name(), pref)?;
+            }
+        }
+    }
 
-        args.remove(index);
+    if !unmet_prefs.is_empty() {
+        write!(assigned_roles, "\n\n**Preferences not met**{}", unmet_prefs)?;
     }
 
     msg.channel_id.say(&ctx.http, assigned_roles.trim()).await?;
@@ -130,6 +174,147 @@ async fn randomize_roles(ctx: &Context, msg: &Message, args: Args) -> CommandRes
     Ok(())
 }
 
+/// Role and faction preferences a player gave when signing up.
+struct Preferences {
+    prefer: Vec<String>,
+    avoid: Vec<String>,
+}
+
+impl Preferences {
+    /// Describes the preferences `role` doesn't meet.
+    fn unmet(&self, role: &str) -> Vec<String> {
+        let mut unmet = Vec::new();
+
+        if !self.prefer.is_empty() && !self.prefer.iter().any(|p| role_matches(role, p)) {
+            unmet.push(format!("wanted {}", self.prefer.join(" or ")));
+        }
+        for avoid in self.avoid.iter().filter(|a| role_matches(role, a)) {
+            unmet.push(format!("didn't want {}", avoid));
+        }
+
+        unmet
+    }
+}
+
+async fn get_preferences(
+    pool: &PgPool,
+    guild_id: GuildId,
+) -> Result<HashMap<i64, Preferences>, sqlx::Error> {
+    let rows = sqlx::query!(
+        "SELECT user_id, prefer, avoid FROM signup_prefs WHERE guild_id = $1;",
+        guild_id.0 as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            (
+                r.user_id,
+                Preferences {
+                    prefer: r.prefer,
+                    avoid: r.avoid,
+                },
+            )
+        })
+        .collect())
+}
+
+/// Checks if a role like `doctor (town)` matches a role or faction name.
+fn role_matches(role: &str, name: &str) -> bool {
+    let (role_name, faction) = match role.rfind('(') {
+        Some(i) if role.ends_with(')') => (&role[..i], Some(&role[i + 1..role.len() - 1])),
+        _ => (role, None),
+    };
+
+    role_name.trim().eq_ignore_ascii_case(name.trim())
+        || matches!(faction, Some(f) if f.trim().eq_ignore_ascii_case(name.trim()))
+}
+
+/// Lists the preferences players gave when signing up.
+///
+/// **Usage:** `[p]prefs`
+///
+/// **Alias:** `preferences`
+///
+/// I need the permission to embed links for this command to work.
+#[command("prefs")]
+#[aliases("preferences")]
+async fn player_prefs(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let res = sqlx::query!(
+        "SELECT player_role_id FROM config WHERE guild_id = $1",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let role = match get_role(ctx, guild.id, res.player_role_id).await {
+        Ok(r) => r,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, "Player role has not been set up.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let prefs = get_preferences(pool, guild.id).await?;
+
+    let mut text = String::new();
+    for player in guild
+        .members
+        .values()
+        .filter(|m| m.roles.contains(&role.id))
+    {
+        let p = match prefs.get(&(player.user.id.0 as i64)) {
+            Some(p) => p,
+            None => continue,
+        };
+
+        let mut parts = Vec::new();
+        if !p.prefer.is_empty() {
+            parts.push(format!("prefers {}", p.prefer.join(" or ")));
+        }
+        if !p.avoid.is_empty() {
+            parts.push(format!("no {}", p.avoid.join(", ")));
+        }
+
+        write!(text, "\n{}: {}", player.mention(), parts.join("; "))?;
+    }
+
+    if text.is_empty() {
+        msg.channel_id
+            .say(&ctx.http, "No player has given any preferences.")
+            .await?;
+        return Ok(());
+    }
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.title("Player Preferences");
+                e.description(text.trim());
+                e.colour(EMBED_COLOUR);
+
+                e
+            });
+
+            m
+        })
+        .await?;
+
+    Ok(())
+}
+
 /// Syncs total sign-ups with number of members with Player role.
 ///
 /// **Usage:** `[p]synctotal`
@@ -259,13 +444,22 @@ async fn total_signups(ctx: &Context, msg: &Message) -> CommandResult {
 
 /// Creates private channels for all members with Player role.
 ///
-/// **Usage:** `[p]playerchats`
+/// **Usage:** `[p]playerchats [--nick | --number] [--topic] [category_name]`
 ///
 /// **Alias:** `pc`
 ///
 /// You can supply a name for the category which is created to keep all the channels.
 /// "Private Chats" is used by default.
 ///
+/// The bot remembers which channel belongs to which player. If you use the command
+/// again, only the missing chats are created. If a category is full, a new one is
+/// created, named like "Private Chats 2".
+///
+/// Channels are named after the username of the player by default. With `--nick`,
+/// they are named after their nickname instead, and with `--number`, after their
+/// sign-up number, like `player-3`. With `--topic`, the topic of every player chat,
+/// including the ones that already exist, is set to the name of the player.
+///
 /// **Note:** Do not delete the private channels created for users who are Mafia.
 /// Anyone can use a custom Discord client to view the complete list of channel names
 /// in a server, irrespective of the permissions. If the find that a few people don't have
@@ -284,7 +478,24 @@ async fn total_signups(ctx: &Context, msg: &Message) -> CommandResult {
 /// The bot asks for confirmation before creating any channels.
 #[command("playerchats")]
 #[aliases("pc")]
-async fn players_chats(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+async fn players_chats(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let mut naming = ChatNaming::Username;
+    let mut sync_topics = false;
+    while let Some(flag) = args.current().filter(|a| a.starts_with("--")) {
+        match flag.to_lowercase().as_str() {
+            "--nick" => naming = ChatNaming::Nickname,
+            "--number" => naming = ChatNaming::Number,
+            "--topic" => sync_topics = true,
+            _ => {
+                msg.channel_id
+                    .say(&ctx.http, format!("Unknown option `{}`.", flag))
+                    .await?;
+                return Ok(());
+            }
+        }
+        args.advance();
+    }
+
     // Optionally specified category name
     let cat_name = args.remains().unwrap_or("Private Chats");
 
@@ -298,7 +509,7 @@ async fn players_chats(ctx: &Context, msg: &Message, args: Args) -> CommandResul
         .say(&ctx.http, "Are you sure you want to create player chats?")
         .await?;
 
-    if !yes_or_no_prompt(&ctx, &confirm_msg, &msg.author, 30.0).await? {
+    if !yes_or_no_prompt(ctx, &confirm_msg, &msg.author, 30.0).await? {
         msg.channel_id
             .say(&ctx.http, "Cancelled player chats creation.")
             .await?;
@@ -308,25 +519,11 @@ async fn players_chats(ctx: &Context, msg: &Message, args: Args) -> CommandResul
     let me = ctx.cache.current_user().await;
     let default_role = RoleId(guild.id.0);
 
-    // Allow hosts and the bot to talk in the category.
-    let cat_perms = vec![
-        PermissionOverwrite {
-            allow: Permissions::empty(),
-            deny: Permissions::READ_MESSAGES,
-            kind: PermissionOverwriteType::Role(default_role),
-        },
-        PermissionOverwrite {
-            allow: Permissions::READ_MESSAGES | Permissions::SEND_MESSAGES,
-            deny: Permissions::empty(),
-            kind: PermissionOverwriteType::Member(me.id),
-        },
-    ];
-
     let data_read = ctx.data.read().await;
     let pool = data_read.get::<ConnectionPool>().unwrap();
 
     let res = match sqlx::query!(
-        "SELECT host_role_id, player_role_id FROM config WHERE guild_id = $1",
+        "SELECT host_role_id, player_role_id, players, pc_category_ids FROM config WHERE guild_id = $1",
         guild.id.0 as i64
     )
     .fetch_one(pool)
@@ -358,29 +555,31 @@ async fn players_chats(ctx: &Context, msg: &Message, args: Args) -> CommandResul
         }
     };
 
-    let category = match guild
-        .create_channel(ctx, |c| {
-            c.name(cat_name)
-                .kind(ChannelType::Category)
-                .permissions(cat_perms)
-        })
-        .await
-    {
-        Ok(r) => r,
-        Err(_) => {
-            msg.channel_id
-                .say(&ctx.http, "Could not create a category.")
-                .await?;
-            return Ok(());
-        }
-    };
-
-    let players: Vec<&Member> = guild
+    let mut players: Vec<&Member> = guild
         .members
         .values()
         .filter(|m| m.roles.contains(&player_role.id))
         .collect();
 
+    // Players are numbered in the order they signed up.
+    let sign_ups = res.players.unwrap_or_default();
+    players.sort_by_key(|m| {
+        sign_ups
+            .iter()
+            .position(|id| *id == m.user.id.0 as i64)
+            .unwrap_or(usize::MAX)
+    });
+
+    let existing: HashMap<i64, i64> = sqlx::query!(
+        "SELECT user_id, channel_id FROM player_chats WHERE guild_id = $1 AND NOT archived;",
+        guild.id.0 as i64
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| (r.user_id, r.channel_id))
+    .collect();
+
     // The following operation may take some time to finish, depending on number of players.
     // So, we make the bot appear as if it's typing something in order to indicate that the process
     // is still going on.
@@ -388,15 +587,110 @@ async fn players_chats(ctx: &Context, msg: &Message, args: Args) -> CommandResul
         c.broadcast_typing(ctx).await?
     }
 
-    // Compute this only once.
-    let allow_perms = Permissions::READ_MESSAGES
-        | Permissions::SEND_MESSAGES
-        | Permissions::ADD_REACTIONS
-        | Permissions::EMBED_LINKS
-        | Permissions::READ_MESSAGE_HISTORY
-        | Permissions::ATTACH_FILES;
+    // Find the players without a chat first, so we know how many chats to create.
+    let mut missing = Vec::new();
+    for (index, player) in players.into_iter().enumerate() {
+        let channel = match existing.get(&(player.user.id.0 as i64)) {
+            Some(id) => get_channel_from_id(ctx, guild.id, Some(*id)).await.ok(),
+            None => None,
+        };
+
+        match channel {
+            Some(mut channel) => {
+                let topic = format!("Private chat of {}.", player.display_name());
+                if sync_topics && channel.topic.as_deref() != Some(topic.as_str()) {
+                    channel.edit(&ctx.http, |c| c.topic(&topic)).await?;
+                }
+            }
+            None => missing.push((index + 1, player)),
+        }
+    }
+
+    if missing.is_empty() {
+        msg.channel_id
+            .say(&ctx.http, "All players already have a chat.")
+            .await?;
+        return Ok(());
+    }
+
+    let mut categories = Vec::new();
+    for id in res.pc_category_ids {
+        if let Ok(c) = get_channel_from_id(ctx, guild.id, Some(id)).await {
+            if c.kind == ChannelType::Category {
+                categories.push(c);
+            }
+        }
+    }
+    // Later categories are named after the first one, like "Private Chats 2".
+    // The first one left may itself be numbered if the original was deleted.
+    let base_name = match categories.first() {
+        Some(c) => match c.name.rsplit_once(' ') {
+            Some((base, n)) if n.parse::<usize>().is_ok() => base.to_string(),
+            _ => c.name.clone(),
+        },
+        None => cat_name.to_string(),
+    };
+
+    let mut channel_counts: HashMap<ChannelId, usize> = HashMap::new();
+    for channel in guild.channels.values() {
+        if let Some(id) = channel.category_id {
+            *channel_counts.entry(id).or_default() += 1;
+        }
+    }
+
+    let total = missing.len();
+    let mut progress = msg
+        .channel_id
+        .say(
+            &ctx.http,
+            format!("Creating player chats... `0/{}` done.", total),
+        )
+        .await?;
+
+    let mut created = 0;
+    for (number, player) in missing {
+        let topic = format!("Private chat of {}.", player.display_name());
+
+        let category = match categories
+            .iter()
+            .find(|c| channel_counts.get(&c.id).copied().unwrap_or(0) < CATEGORY_CHANNEL_LIMIT)
+        {
+            Some(c) => c.clone(),
+            None => {
+                let name = match categories.len() {
+                    0 => base_name.clone(),
+                    n => format!("{} {}", base_name, n + 1),
+                };
+                let c = match create_pc_category(ctx, &guild, &name, me.id).await {
+                    Some(c) => c,
+                    None => {
+                        msg.channel_id
+                            .say(
+                                &ctx.http,
+                                format!(
+                                    "Could not create a category after creating {} of {} chats. \
+                                    Use the command again to create the rest.",
+                                    created, total
+                                ),
+                            )
+                            .await?;
+                        return Ok(());
+                    }
+                };
+
+                sqlx::query!(
+                    "UPDATE config SET pc_category_ids = array_append(pc_category_ids, $2) WHERE guild_id = $1;",
+                    guild.id.0 as i64,
+                    c.id.0 as i64
+                )
+                .execute(pool)
+                .await?;
+
+                categories.push(c.clone());
+                c
+            }
+        };
 
-    for player in players {
         let mut overwrites = vec![
             PermissionOverwrite {
                 allow: Permissions::empty(),
@@ -411,7 +705,7 @@ async fn players_chats(ctx: &Context, msg: &Message, args: Args) -> CommandResul
                 kind: PermissionOverwriteType::Member(me.id),
             },
             PermissionOverwrite {
-                allow: allow_perms,
+                allow: FACTION_CHAT_PERMS,
                 deny: Permissions::empty(),
                 kind: PermissionOverwriteType::Member(player.user.id),
             },
@@ -419,86 +713,131 @@ async fn players_chats(ctx: &Context, msg: &Message, args: Args) -> CommandResul
 
         if let Some(r) = &host_role {
             overwrites.push(PermissionOverwrite {
-                allow: allow_perms,
+                allow: FACTION_CHAT_PERMS,
                 deny: Permissions::empty(),
                 kind: PermissionOverwriteType::Role(r.id),
             })
         };
 
-        match guild
+        let name = naming.channel_name(player, number);
+
+        let channel = match guild
             .create_channel(ctx, |c| {
-                c.name(&player.user.name)
+                c.name(&name)
                     .kind(ChannelType::Text)
                     .permissions(overwrites)
-                    .category(&category)
+                    .category(&category);
+                if sync_topics {
+                    c.topic(&topic);
+                }
+                c
             })
             .await
         {
-            Ok(_) => (),
+            Ok(c) => c,
             Err(_) => {
-                // We were able to create a category earlier, but we're still
-                // checking again to be absolutely sure.
+                // Chats created so far are kept, since using the command again
+                // carries on from where it stopped.
                 msg.channel_id
                     .say(
                         &ctx.http,
-                        "I couldn't create a channel. Please check my permissions.",
+                        format!(
+                            "I couldn't create a channel after creating {} of {} chats. Please \
+                            check my permissions and use the command again to create the rest.",
+                            created, total
+                        ),
                     )
                     .await?;
                 return Ok(());
             }
         };
-    }
 
-    msg.channel_id
-        .say(&ctx.http, "Created player chats.")
+        sqlx::query!(
+            "
+            INSERT INTO player_chats(guild_id, user_id, channel_id) VALUES($1, $2, $3)
+            ON CONFLICT (guild_id, user_id)
+            DO UPDATE SET channel_id = $3, archived = false, created_at = now();
+            ",
+            guild.id.0 as i64,
+            player.user.id.0 as i64,
+            channel.id.0 as i64
+        )
+        .execute(pool)
         .await?;
+
+        *channel_counts.entry(category.id).or_default() += 1;
+        created += 1;
+
+        if created % 5 == 0 && created < total {
+            let _ = progress
+                .edit(&ctx.http, |m| {
+                    m.content(format!(
+                        "Creating player chats... `{}/{}` done.",
+                        created, total
+                    ))
+                })
+                .await;
+        }
+    }
+
+    let _ = progress
+        .edit(&ctx.http, |m| {
+            m.content(format!("Created `{}/{}` player chats.", created, total))
+        })
+        .await;
+
     Ok(())
 }
 
-/// Creates a private channel for spectators.
-///
-/// **Usage:** `[p]specchat`
-///
-/// **Alias:** `spectatorchat`
-///
-/// The channel is called `spectator-chat`. Spectator role is required for this
-/// command to work.
-#[command("specchat")]
-#[aliases("spectatorchat")]
-async fn spec_chat(ctx: &Context, msg: &Message) -> CommandResult {
-    let guild = match msg.guild(ctx).await {
-        Some(i) => i,
-        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
-    };
+/// Maximum number of channels Discord allows in a category.
+const CATEGORY_CHANNEL_LIMIT: usize = 50;
 
-    // Get spec role.
-    let data_read = ctx.data.read().await;
-    let pool = data_read.get::<ConnectionPool>().unwrap();
+/// How the channels created by `playerchats` are named.
+enum ChatNaming {
+    Username,
+    Nickname,
+    Number,
+}
 
-    let role = match sqlx::query!(
-        "SELECT spec_role_id FROM config WHERE guild_id = $1",
-        guild.id.0 as i64
-    )
-    .fetch_one(pool)
-    .await
-    {
-        Ok(i) => match get_role(ctx, guild.id, i.spec_role_id).await {
-            Ok(r) => r,
-            Err(_) => {
-                msg.channel_id
-                    .say(&ctx.http, "Spectator role doesn't exist.")
-                    .await?;
-                return Ok(());
+impl ChatNaming {
+    /// Gets a valid channel name for a player.
+    ///
+    /// Falls back to the player's number if their name has no characters
+    /// that can be used in a channel name.
+    fn channel_name(&self, player: &Member, number: usize) -> String {
+        let name = match self {
+            Self::Username => player.user.name.clone(),
+            Self::Nickname => player.display_name().into_owned(),
+            Self::Number => String::new(),
+        };
+
+        let mut channel_name = String::new();
+        for c in name.trim().chars().flat_map(char::to_lowercase) {
+            if c.is_alphanumeric() || c == '_' {
+                channel_name.push(c);
+            } else if (c == '-' || c.is_whitespace()) && !channel_name.ends_with('-') {
+                channel_name.push('-');
             }
-        },
-        Err(_) => {
-            return Err(CommandError::from(
-                "Couldn't fetch details from the database.",
-            ))
         }
-    };
+        let channel_name: String = channel_name.trim_matches('-').chars().take(100).collect();
 
-    let perms = vec![
+        if channel_name.is_empty() {
+            format!("player-{}", number)
+        } else {
+            channel_name
+        }
+    }
+}
+
+/// Creates the category player chats are kept in.
+async fn create_pc_category(
+    ctx: &Context,
+    guild: &Guild,
+    name: &str,
+    bot_id: UserId,
+) -> Option<GuildChannel> {
+    // Allow hosts and the bot to talk in the category.
+    let cat_perms = vec![
         PermissionOverwrite {
             allow: Permissions::empty(),
             deny: Permissions::READ_MESSAGES,
@@ -507,20 +846,218 @@ async fn spec_chat(ctx: &Context, msg: &Message) -> CommandResult {
         PermissionOverwrite {
             allow: Permissions::READ_MESSAGES | Permissions::SEND_MESSAGES,
             deny: Permissions::empty(),
-            kind: PermissionOverwriteType::Role(role.id),
+            kind: PermissionOverwriteType::Member(bot_id),
         },
     ];
 
-    let channel = match guild
-        .create_channel(&ctx.http, |c| {
-            c.name("spectator-chat")
-                .kind(ChannelType::Text)
-                .permissions(perms)
+    guild
+        .create_channel(ctx, |c| {
+            c.name(name)
+                .kind(ChannelType::Category)
+                .permissions(cat_perms)
         })
         .await
-    {
-        Ok(c) => c,
-        Err(_) => {
+        .ok()
+}
+
+/// Deletes player chats.
+///
+/// **Usage:** `[p]pcdelete [player_1 [player_2 [...]]]`
+///
+/// Only chats created by the `playerchats` command can be deleted. If no
+/// players are given, the chats of all players are deleted.
+///
+/// The bot asks for confirmation before deleting any channels.
+#[command("pcdelete")]
+async fn pc_delete(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    remove_player_chats(ctx, msg, args, true).await
+}
+
+/// Archives player chats.
+///
+/// **Usage:** `[p]pcarchive [player_1 [player_2 [...]]]`
+///
+/// The players can still read their chats, but they can't talk in them
+/// anymore. If no players are given, the chats of all players are archived.
+#[command("pcarchive")]
+async fn pc_archive(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    remove_player_chats(ctx, msg, args, false).await
+}
+
+async fn remove_player_chats(
+    ctx: &Context,
+    msg: &Message,
+    args: Args,
+    delete: bool,
+) -> CommandResult {
+    let guild_id = match msg.guild_id {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let members = match parse_members(ctx, msg, guild_id, args).await? {
+        Some(m) => m,
+        None => return Ok(()),
+    };
+    let user_ids: Vec<i64> = members.iter().map(|m| m.user.id.0 as i64).collect();
+
+    if delete {
+        let confirm_msg = msg
+            .channel_id
+            .say(&ctx.http, "Are you sure you want to delete player chats?")
+            .await?;
+
+        if !yes_or_no_prompt(ctx, &confirm_msg, &msg.author, 30.0).await? {
+            msg.channel_id
+                .say(&ctx.http, "Cancelled deleting player chats.")
+                .await?;
+            return Ok(());
+        }
+    }
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let chats = sqlx::query!(
+        "
+        SELECT channel_id FROM player_chats
+        WHERE guild_id = $1 AND (cardinality($2::bigint[]) = 0 OR user_id = ANY($2))
+        AND ($3 OR NOT archived);
+        ",
+        guild_id.0 as i64,
+        &user_ids,
+        delete
+    )
+    .fetch_all(pool)
+    .await?;
+
+    if chats.is_empty() {
+        msg.channel_id
+            .say(&ctx.http, "There are no player chats to change.")
+            .await?;
+        return Ok(());
+    }
+
+    let bot_id = ctx.cache.current_user_id().await;
+    let mut count = 0;
+    for chat in chats {
+        if let Ok(channel) = get_channel_from_id(ctx, guild_id, Some(chat.channel_id)).await {
+            let res = if delete {
+                channel.delete(&ctx.http).await.map(|_| ())
+            } else {
+                lock_channel(ctx, &channel, bot_id).await
+            };
+
+            if res.is_err() {
+                msg.channel_id
+                    .say(
+                        &ctx.http,
+                        format!(
+                            "I couldn't change {}. Please check my permissions.",
+                            channel.mention()
+                        ),
+                    )
+                    .await?;
+                return Ok(());
+            }
+        }
+
+        if delete {
+            sqlx::query!(
+                "DELETE FROM player_chats WHERE guild_id = $1 AND channel_id = $2;",
+                guild_id.0 as i64,
+                chat.channel_id
+            )
+            .execute(pool)
+            .await?;
+        } else {
+            sqlx::query!(
+                "UPDATE player_chats SET archived = true WHERE guild_id = $1 AND channel_id = $2;",
+                guild_id.0 as i64,
+                chat.channel_id
+            )
+            .execute(pool)
+            .await?;
+        }
+        count += 1;
+    }
+
+    let reply = if delete {
+        format!("Deleted {} player chats.", count)
+    } else {
+        format!("Archived {} player chats.", count)
+    };
+    msg.channel_id.say(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+/// Creates a private channel for spectators.
+///
+/// **Usage:** `[p]specchat`
+///
+/// **Alias:** `spectatorchat`
+///
+/// The channel is called `spectator-chat`. Spectator role is required for this
+/// command to work.
+#[command("specchat")]
+#[aliases("spectatorchat")]
+async fn spec_chat(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    // Get spec role.
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let role = match sqlx::query!(
+        "SELECT spec_role_id FROM config WHERE guild_id = $1",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(i) => match get_role(ctx, guild.id, i.spec_role_id).await {
+            Ok(r) => r,
+            Err(_) => {
+                msg.channel_id
+                    .say(&ctx.http, "Spectator role doesn't exist.")
+                    .await?;
+                return Ok(());
+            }
+        },
+        Err(_) => {
+            return Err(CommandError::from(
+                "Couldn't fetch details from the database.",
+            ))
+        }
+    };
+
+    let perms = vec![
+        PermissionOverwrite {
+            allow: Permissions::empty(),
+            deny: Permissions::READ_MESSAGES,
+            kind: PermissionOverwriteType::Role(RoleId(guild.id.0)),
+        },
+        PermissionOverwrite {
+            allow: Permissions::READ_MESSAGES | Permissions::SEND_MESSAGES,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Role(role.id),
+        },
+    ];
+
+    let channel = match guild
+        .create_channel(&ctx.http, |c| {
+            c.name("spectator-chat")
+                .kind(ChannelType::Text)
+                .permissions(perms)
+        })
+        .await
+    {
+        Ok(c) => c,
+        Err(_) => {
             msg.channel_id
                 .say(&ctx.http, "I'm unable to create a channel.")
                 .await?;
@@ -535,17 +1072,17 @@ async fn spec_chat(ctx: &Context, msg: &Message) -> CommandResult {
     Ok(())
 }
 
-/// Creates a private channel for specified mafia members.
+/// Creates a faction chat called `mafia` for specified mafia members.
 ///
 /// **Usage:** `[p]mafiachat <mafia_1 [mafia_2 [...]]>`
 ///
 /// **Alias:** `mafchat`
 ///
+/// This is the same as `[p]factionchat mafia <mafia_1 [mafia_2 [...]]>`.
+///
 /// You must supply the exact name, mention or ID of **each** mafia member
 /// to enable them to see the resultant channel.
 ///
-/// The created channel will be called `mafia-chat`.
-///
 /// **Example**
 ///
 /// *Assuming `Arius#5544`, `Ligi#1241` and `Craw#4421` are mafia.*
@@ -553,52 +1090,114 @@ async fn spec_chat(ctx: &Context, msg: &Message) -> CommandResult {
 ///
 /// Command: `[p]mafchat 324967676655173642 Ligi Craw#4421`
 ///
-/// Result: The bot will create a channel called "mafia-chat". The hosts,
-/// `Arius`, `Ligi`, `Craw` and the bot will be able to see the channel.
+/// Result: The bot will create a channel called "mafia" in the game category.
+/// The hosts, `Arius`, `Ligi`, `Craw` and the bot will be able to see the channel.
 #[command("mafiachat")]
 #[aliases("mafchat")]
 #[min_args(1)]
-async fn mafia_chat(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+async fn mafia_chat(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    create_faction_chat(ctx, msg, "mafia", args).await
+}
+
+/// Creates a private chat for a faction.
+///
+/// **Usage:** `[p]factionchat <name> <member_1 [member_2 [...]]>`
+///
+/// **Alias:** `fc`
+///
+/// The channel is created in the game category. Hosts can see it too. You
+/// must supply the exact name, mention or ID of **each** member. Use quotes
+/// around names with spaces.
+///
+/// **Example**
+///
+/// Command: `[p]factionchat vampires Arius Ligi`
+///
+/// Result: The bot will create a channel called "vampires" which only
+/// `Arius`, `Ligi`, the hosts and the bot can see.
+#[command("factionchat")]
+#[aliases("fc")]
+#[min_args(2)]
+async fn faction_chat(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let name = args.single_quoted::<String>()?;
+    create_faction_chat(ctx, msg, &name, args).await
+}
+
+async fn create_faction_chat(
+    ctx: &Context,
+    msg: &Message,
+    name: &str,
+    args: Args,
+) -> CommandResult {
     let guild = match msg.guild(ctx).await {
         Some(i) => i,
         None => return Err(CommandError::from("Couldn't fetch details of this server.")),
     };
 
-    // Parse args to get users.
-    let mut members = Vec::new();
-    for arg in args.iter() {
-        // members.push(value: T)
-        let parsed_arg = match arg {
-            Ok(a) => a,
-            Err(_) => continue,
-        };
-        match get_member(ctx, guild.id, Some(&parsed_arg)).await {
-            Ok(m) => members.push(m),
-            Err(_) => {
-                msg.channel_id
-                    .say(&ctx.http, format!("No member found from {}.", &parsed_arg))
-                    .await?;
-                return Ok(());
-            }
-        }
+    let members = match parse_members(ctx, msg, guild.id, args).await? {
+        Some(m) => m,
+        None => return Ok(()),
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    if get_faction_chat(ctx, guild.id, pool, name).await.is_some() {
+        msg.channel_id
+            .say(
+                &ctx.http,
+                format!(
+                    "The `{}` faction chat already exists. Use `factionadd` to add members to it.",
+                    name
+                ),
+            )
+            .await?;
+        return Ok(());
     }
 
-    let mut perms = vec![PermissionOverwrite {
-        allow: Permissions::empty(),
-        deny: Permissions::READ_MESSAGES,
-        kind: PermissionOverwriteType::Role(RoleId(guild.id.0)),
-    }];
+    let category = match get_game_category(ctx, &guild, pool).await {
+        Ok(c) => c,
+        Err(e) => return Err(CommandError::from(e)),
+    };
+
+    let mut perms = vec![
+        PermissionOverwrite {
+            allow: Permissions::empty(),
+            deny: Permissions::READ_MESSAGES,
+            kind: PermissionOverwriteType::Role(RoleId(guild.id.0)),
+        },
+        PermissionOverwrite {
+            allow: Permissions::READ_MESSAGES
+                | Permissions::SEND_MESSAGES
+                | Permissions::ADD_REACTIONS,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Member(ctx.cache.current_user_id().await),
+        },
+    ];
+
+    let res = sqlx::query!(
+        "SELECT host_role_id, dead_role_id, dead_see_chats FROM config WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await?;
+
+    if let Some(id) = res.host_role_id {
+        perms.push(PermissionOverwrite {
+            allow: FACTION_CHAT_PERMS,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Role(RoleId(id as u64)),
+        });
+    }
 
-    let allow_perms = Permissions::READ_MESSAGES
-        | Permissions::SEND_MESSAGES
-        | Permissions::ADD_REACTIONS
-        | Permissions::EMBED_LINKS
-        | Permissions::READ_MESSAGE_HISTORY
-        | Permissions::ATTACH_FILES;
+    // Players who are already dead can read chats created after their death too.
+    if let (true, Some(id)) = (res.dead_see_chats, res.dead_role_id) {
+        perms.push(dead_view_overwrite(RoleId(id as u64)));
+    }
 
-    for member in members {
+    for member in &members {
         perms.push(PermissionOverwrite {
-            allow: allow_perms,
+            allow: FACTION_CHAT_PERMS,
             deny: Permissions::empty(),
             kind: PermissionOverwriteType::Member(member.user.id),
         });
@@ -606,9 +1205,10 @@ async fn mafia_chat(ctx: &Context, msg: &Message, mut args: Args) -> CommandResu
 
     let channel = match guild
         .create_channel(&ctx.http, |c| {
-            c.name("mafia-chat")
+            c.name(name)
                 .kind(ChannelType::Text)
                 .permissions(perms)
+                .category(&category)
         })
         .await
     {
@@ -621,6 +1221,15 @@ async fn mafia_chat(ctx: &Context, msg: &Message, mut args: Args) -> CommandResu
         }
     };
 
+    sqlx::query!(
+        "INSERT INTO faction_chats(guild_id, name, channel_id) VALUES($1, $2, $3);",
+        guild.id.0 as i64,
+        name,
+        channel.id.0 as i64
+    )
+    .execute(pool)
+    .await?;
+
     msg.channel_id
         .say(&ctx.http, format!("Created {}.", channel.mention()))
         .await?;
@@ -628,123 +1237,271 @@ async fn mafia_chat(ctx: &Context, msg: &Message, mut args: Args) -> CommandResu
     Ok(())
 }
 
-/// Creates a category for a new cycle with day, votes and night channels.
+/// Adds members to a faction chat.
 ///
-/// **Usage:** `[p]cycle [number]`
+/// **Usage:** `[p]factionadd <name> <member_1 [member_2 [...]]>`
 ///
-/// If you use the bot to create all cycle channels, the bot will be able to detect
-/// the correct cycle number. If you didn't use the bot to create all cycles,
-/// please specify the cycle number in the command.
+/// **Example**
 ///
-/// The category is named "Cycle x". The day, votes and night channels will be
-/// called "day-x", "day-x-voting" and "night-x" respectively.
+/// Command: `[p]factionadd vampires Craw`
 ///
-/// Day and votes channels will be visible to everyone, and all Players will be able
-/// to write in it. Night channel will remain hidden.
+/// Result: `Craw` can now see and talk in the "vampires" channel.
+#[command("factionadd")]
+#[min_args(2)]
+async fn faction_add(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    edit_faction_chat(ctx, msg, args, true).await
+}
+
+/// Removes members from a faction chat.
 ///
-/// *x is the cycle number*
+/// **Usage:** `[p]factionremove <name> <member_1 [member_2 [...]]>`
 ///
-/// The bot asks for confirmation before creating the channels.
-#[command("cycle")]
-async fn create_cycle(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
-    let guild = match msg.guild(ctx).await {
+/// **Alias:** `factionrm`
+#[command("factionremove")]
+#[aliases("factionrm")]
+#[min_args(2)]
+async fn faction_remove(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    edit_faction_chat(ctx, msg, args, false).await
+}
+
+async fn edit_faction_chat(
+    ctx: &Context,
+    msg: &Message,
+    mut args: Args,
+    add: bool,
+) -> CommandResult {
+    let guild_id = match msg.guild_id {
         Some(i) => i,
         None => return Err(CommandError::from("Couldn't fetch details of this server.")),
     };
 
+    let name = args.single_quoted::<String>()?;
+
+    let members = match parse_members(ctx, msg, guild_id, args).await? {
+        Some(m) => m,
+        None => return Ok(()),
+    };
+
     let data_read = ctx.data.read().await;
     let pool = data_read.get::<ConnectionPool>().unwrap();
 
-    let data = match sqlx::query_as_unchecked!(
-        Data,
-        "SELECT player_role_id, cycle FROM config WHERE guild_id = $1",
-        guild.id.0 as i64
-    )
-    .fetch_one(pool)
-    .await
-    {
-        Ok(d) => d,
-        Err(_) => {
-            return Err(CommandError::from(
-                "Couldn't fetch details from the database.",
-            ))
+    let channel = match get_faction_chat(ctx, guild_id, pool, &name).await {
+        Some(c) => c,
+        None => {
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    format!("No faction chat called `{}` was found.", name),
+                )
+                .await?;
+            return Ok(());
         }
     };
 
-    let cycle = match data.cycle {
-        Some(c) => c.0,
-        None => Cycle {
-            number: 0,
-            day: None,
-            night: None,
-            votes: None,
-        },
-    };
+    for member in &members {
+        let kind = PermissionOverwriteType::Member(member.user.id);
+        let res = if add {
+            channel
+                .create_permission(
+                    &ctx.http,
+                    &PermissionOverwrite {
+                        allow: FACTION_CHAT_PERMS,
+                        deny: Permissions::empty(),
+                        kind,
+                    },
+                )
+                .await
+        } else {
+            channel.delete_permission(&ctx.http, kind).await
+        };
 
-    // Parse args to check if user supplied a cycle number.
-    // If not, use number in database after adding one.
-    let number: i16 = match args.message().parse() {
-        Ok(n) => n,
-        Err(_) => cycle.number + 1,
-    };
+        if res.is_err() {
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    format!(
+                        "I couldn't change the permissions of {}. Please check my permissions.",
+                        member.display_name()
+                    ),
+                )
+                .await?;
+            return Ok(());
+        }
+    }
 
-    // Confirmation for cycle creation.
-    let confirm_msg = msg
-        .channel_id
-        .say(
-            &ctx.http,
-            format!(
-                "
-                Are you sure you want to create cycle `{}` channels? Make \
-                sure you have the day text ready. Users will be able to talk \
-                in the day and vote channels as soon as they are created.
-                ",
-                number
-            )
-            .trim(),
+    let reply = if add {
+        format!("Added {} members to {}.", members.len(), channel.mention())
+    } else {
+        format!(
+            "Removed {} members from {}.",
+            members.len(),
+            channel.mention()
         )
-        .await?;
+    };
+    msg.channel_id.say(&ctx.http, reply).await?;
 
-    if !yes_or_no_prompt(&ctx, &confirm_msg, &msg.author, 30.0).await? {
+    Ok(())
+}
+
+/// Lists all faction chats of the game.
+///
+/// **Usage:** `[p]factionchats`
+#[command("factionchats")]
+async fn faction_chats(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = match msg.guild_id {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let chats = sqlx::query!(
+        "
+        SELECT name, channel_id FROM faction_chats
+        WHERE guild_id = $1 ORDER BY created_at;
+        ",
+        guild_id.0 as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    if chats.is_empty() {
         msg.channel_id
-            .say(&ctx.http, "Cancelled cycle creation.")
+            .say(&ctx.http, "There are no faction chats.")
             .await?;
         return Ok(());
     }
 
-    // User confirmed. Let's do it.
-    let role = match get_role(ctx, guild.id, data.player_role_id).await {
-        Ok(r) => r,
-        Err(_) => {
+    let mut text = String::from("**Faction Chats**");
+    for chat in chats {
+        write!(text, "\n{}: <#{}>", chat.name, chat.channel_id)?;
+    }
+
+    msg.channel_id.say(&ctx.http, text).await?;
+
+    Ok(())
+}
+
+/// Creates a hidden chat for some players that is locked on the next cycle.
+///
+/// **Usage:** `[p]tempchat <name> <member_1 [member_2 [...]]> [--phase] [--anon <label>]`
+///
+/// The channel is created in the current cycle category. Only the members,
+/// hosts and the bot can see it. When the `cycle` command is used, the
+/// members can still read the channel but can't talk in it anymore. With
+/// `--phase`, the chat is locked when the phase changes instead, which also
+/// happens when the `night` command is used.
+///
+/// With `--anon <label>`, the first member isn't added to the channel. Their
+/// private chat is linked to it instead, and their messages are shown under
+/// `label`.
+///
+/// **Example**
+///
+/// Command: `[p]tempchat jail Arius Ligi --anon Jailor`
+///
+/// Result: The bot will create a channel called "jail" which `Ligi` can see.
+/// Whatever `Arius` says in their private chat is posted in it as "Jailor".
+#[command("tempchat")]
+#[min_args(2)]
+async fn temp_chat(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let name = args.single_quoted::<String>()?;
+
+    let mut members = Vec::new();
+    let mut until_phase_end = false;
+    let mut label = None;
+    while !args.is_empty() {
+        let arg = args.single_quoted::<String>()?;
+        match arg.to_lowercase().as_str() {
+            "--phase" => until_phase_end = true,
+            "--anon" => match args.single_quoted::<String>() {
+                Ok(l) if !l.is_empty() && l.chars().count() <= 80 => label = Some(l),
+                _ => {
+                    msg.channel_id
+                        .say(
+                            &ctx.http,
+                            "Please give a label between 1 and 80 characters long after `--anon`.",
+                        )
+                        .await?;
+                    return Ok(());
+                }
+            },
+            _ => match get_member(ctx, guild.id, Some(&arg)).await {
+                Ok(m) => members.push(m),
+                Err(_) => {
+                    msg.channel_id
+                        .say(&ctx.http, format!("No member found from {}.", arg))
+                        .await?;
+                    return Ok(());
+                }
+            },
+        }
+    }
+
+    if members.is_empty() {
+        msg.channel_id
+            .say(&ctx.http, "Please give at least one member.")
+            .await?;
+        return Ok(());
+    }
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    // The anonymous member talks from their private chat.
+    let mut relay_chat = None;
+    if label.is_some() {
+        let member = members.remove(0);
+        let bot_id = ctx.cache.current_user_id().await;
+        match find_private_chat(pool, &guild, member.user.id, bot_id).await {
+            Some(c) => relay_chat = Some(c),
+            None => {
+                msg.channel_id
+                    .say(
+                        &ctx.http,
+                        format!(
+                            "I couldn't find the private chat of {}.",
+                            member.display_name()
+                        ),
+                    )
+                    .await?;
+                return Ok(());
+            }
+        }
+    }
+
+    let res = sqlx::query_as_unchecked!(
+        HostCycle,
+        "SELECT host_role_id, cycle FROM config WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let category = match res.cycle {
+        Some(c) => match get_channel_from_id(ctx, guild.id, c.day).await {
+            Ok(day) => day.category_id,
+            Err(_) => None,
+        },
+        None => None,
+    };
+    let category = match category {
+        Some(c) => c,
+        None => {
             msg.channel_id
-                .say(&ctx.http, "Player role doesn't exist or is invalid now.")
+                .say(&ctx.http, "I couldn't find the current cycle category.")
                 .await?;
             return Ok(());
         }
     };
 
-    let me = ctx.cache.current_user().await;
-    let default_role = RoleId(guild.id.0);
-
-    let perms = vec![
-        PermissionOverwrite {
-            allow: Permissions::READ_MESSAGES | Permissions::ADD_REACTIONS,
-            deny: Permissions::SEND_MESSAGES,
-            kind: PermissionOverwriteType::Role(default_role),
-        },
-        PermissionOverwrite {
-            allow: Permissions::SEND_MESSAGES,
-            deny: Permissions::ATTACH_FILES,
-            kind: PermissionOverwriteType::Role(role.id),
-        },
-        PermissionOverwrite {
-            allow: Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS,
-            deny: Permissions::empty(),
-            kind: PermissionOverwriteType::Member(me.id),
-        },
-    ];
-
-    let night_perms = vec![
+    let mut perms = vec![
         PermissionOverwrite {
             allow: Permissions::empty(),
             deny: Permissions::READ_MESSAGES,
@@ -755,696 +1512,2831 @@ async fn create_cycle(ctx: &Context, msg: &Message, args: Args) -> CommandResult
                 | Permissions::SEND_MESSAGES
                 | Permissions::EMBED_LINKS,
             deny: Permissions::empty(),
-            kind: PermissionOverwriteType::Member(me.id),
+            kind: PermissionOverwriteType::Member(ctx.cache.current_user_id().await),
         },
     ];
 
-    let category = match guild
+    if let Some(id) = res.host_role_id {
+        perms.push(PermissionOverwrite {
+            allow: FACTION_CHAT_PERMS,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Role(RoleId(id as u64)),
+        });
+    }
+
+    for member in &members {
+        perms.push(PermissionOverwrite {
+            allow: FACTION_CHAT_PERMS,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Member(member.user.id),
+        });
+    }
+
+    let channel = match guild
         .create_channel(&ctx.http, |c| {
-            c.name(format!("Cycle {}", number))
-                .kind(ChannelType::Category)
+            c.name(&name)
+                .kind(ChannelType::Text)
                 .permissions(perms)
+                .category(category)
         })
         .await
     {
         Ok(c) => c,
         Err(_) => {
             msg.channel_id
-                .say(&ctx.http, "I'm unable to create a category.")
+                .say(&ctx.http, "I'm unable to create a channel.")
                 .await?;
             return Ok(());
         }
     };
 
-    // As the bot was able to create a category, we can assume it's permissions
-    // won't change in such short duration.
-    let day = guild
-        .create_channel(&ctx.http, |c| {
-            c.name(format!("day-{}", number))
-                .kind(ChannelType::Text)
-                .category(&category)
-        })
-        .await?;
-
-    let votes = guild
-        .create_channel(&ctx.http, |c| {
-            c.name(format!("day-{}-voting", number))
-                .kind(ChannelType::Text)
-                .category(&category)
-        })
-        .await?;
-
-    let night = guild
-        .create_channel(&ctx.http, |c| {
-            c.name(format!("night-{}", number))
-                .kind(ChannelType::Text)
-                .category(&category)
-                .permissions(night_perms)
-        })
-        .await?;
-
-    // Time to update the database.
     sqlx::query!(
-        r#"
-        INSERT INTO config(
-            guild_id,
-            cycle,
-            na_submitted
-        ) VALUES (
-            $1,
-            $2,
-            null
-        ) ON CONFLICT (guild_id)
-        DO UPDATE SET
-            cycle = $2,
-            na_submitted = null;
-        "#,
+        "INSERT INTO temp_chats(guild_id, name, channel_id, until_phase_end) VALUES($1, $2, $3, $4);",
         guild.id.0 as i64,
-        serde_json::to_value(Cycle {
-            number: number,
-            day: Some(day.id.0 as i64),
-            night: Some(night.id.0 as i64),
-            votes: Some(votes.id.0 as i64)
-        })
-        .unwrap()
+        channel.name,
+        channel.id.0 as i64,
+        until_phase_end
     )
     .execute(pool)
     .await?;
 
-    msg.channel_id
-        .say(
-            &ctx.http,
-            format!("Created cycle `{}` category and channels!", number),
+    if let Some(relay_chat) = relay_chat {
+        let res = sqlx::query!(
+            "
+            INSERT INTO channel_links(
+                guild_id, first_channel_id, second_channel_id, first_name, until_cycle
+            ) VALUES($1, $2, $3, $4, $5);
+            ",
+            guild.id.0 as i64,
+            relay_chat.id.0 as i64,
+            channel.id.0 as i64,
+            label,
+            !until_phase_end
         )
+        .execute(pool)
+        .await;
+
+        if res.is_err() {
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    format!(
+                        "Created {}, but {} is already linked. Use `unlink` first.",
+                        channel.mention(),
+                        relay_chat.mention()
+                    ),
+                )
+                .await?;
+            return Ok(());
+        }
+    }
+
+    msg.channel_id
+        .say(&ctx.http, format!("Created {}.", channel.mention()))
         .await?;
 
     Ok(())
 }
 
-/// Closes the day channels and opens the night channel.
-///
-/// **Usage:** `[p]night`
-///
-/// Day and votes channels will remain visible to everyone but Players won't be
-/// able to write in them. Night channel will become visible to everyone, and Players
-/// will be able to write in it.
-///
-/// If the Night Actions channel is set up, the bot will send a message marking the
-/// beginning of night x, where x is the cycle number. If Night Actions channel is not
-/// set up, the bot will create a new channel called "night-actions" and send the message
-/// in it. The channel will be visible by hosts and the bot only.
-///
-/// The bot asks for confirmation before executing the command.
-#[command]
-async fn night(ctx: &Context, msg: &Message) -> CommandResult {
-    let guild = match msg.guild(ctx).await {
-        Some(i) => i,
-        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
-    };
-
-    let data_read = ctx.data.read().await;
-    let pool = data_read.get::<ConnectionPool>().unwrap();
+struct HostCycle {
+    host_role_id: Option<i64>,
+    cycle: Option<Json<Cycle>>,
+}
 
-    let data = match sqlx::query_as_unchecked!(
-        Data,
-        "SELECT player_role_id, cycle FROM config WHERE guild_id = $1",
-        guild.id.0 as i64
+/// Locks the temporary chats of a guild so their members can't talk anymore.
+///
+/// Chats made to last until the end of the phase are always locked. Others
+/// are only locked if `cycle_end` is `true`.
+async fn close_temp_chats(ctx: &Context, guild_id: GuildId, pool: &PgPool, cycle_end: bool) {
+    let chats = match sqlx::query!(
+        "
+        SELECT channel_id FROM temp_chats
+        WHERE guild_id = $1 AND NOT archived AND ($2 OR until_phase_end);
+        ",
+        guild_id.0 as i64,
+        cycle_end
     )
-    .fetch_one(pool)
+    .fetch_all(pool)
     .await
     {
-        Ok(d) => d,
-        Err(_) => {
-            return Err(CommandError::from(
-                "Couldn't fetch details from the database.",
-            ))
+        Ok(c) => c,
+        Err(why) => {
+            tracing::error!("Unable to fetch temporary chats: {}", why);
+            return;
         }
     };
 
-    let cycle: Cycle = match data.cycle {
-        Some(c) => c.0,
-        None => {
-            return Err(CommandError::from(
-                "I couldn't get cycle details in the database.",
-            ))
+    let bot_id = ctx.cache.current_user_id().await;
+    for chat in chats {
+        // Chats that were deleted have nothing left to lock.
+        if let Ok(channel) = get_channel_from_id(ctx, guild_id, Some(chat.channel_id)).await {
+            if let Err(why) = lock_channel(ctx, &channel, bot_id).await {
+                tracing::error!("Unable to lock temporary chat {}: {}", channel.id.0, why);
+                continue;
+            }
         }
-    };
 
-    // Confirmation for cycle creation.
-    let confirm_msg = msg
-        .channel_id
-        .say(
-            &ctx.http,
-            format!(
-                "
-                Are you sure you want to start night `{}`? Make \
-                sure you have already posted the night-starting text. \
-                Users will be able to talk in the night channel as soon \
-                as the channel is opened.
-                ",
-                cycle.number
-            )
-            .trim(),
+        let res = sqlx::query!(
+            "UPDATE temp_chats SET archived = true WHERE guild_id = $1 AND channel_id = $2;",
+            guild_id.0 as i64,
+            chat.channel_id
         )
-        .await?;
+        .execute(pool)
+        .await;
 
-    if !yes_or_no_prompt(&ctx, &confirm_msg, &msg.author, 30.0).await? {
-        msg.channel_id
-            .say(&ctx.http, "Cancelled starting of night.")
-            .await?;
-        return Ok(());
+        if let Err(why) = res {
+            tracing::error!("Unable to archive temporary chat: {}", why);
+        }
     }
+}
 
-    let day: GuildChannel = match get_channel_from_id(ctx, guild.id, cycle.day).await {
-        Ok(c) => c,
-        Err(_) => {
-            msg.channel_id
-                .say(&ctx.http, "I couldn't get the current day channel.")
-                .await?;
-            return Ok(());
-        }
-    };
-    let votes: GuildChannel = match get_channel_from_id(ctx, guild.id, cycle.votes).await {
-        Ok(c) => c,
-        Err(_) => {
-            msg.channel_id
-                .say(&ctx.http, "I couldn't get the current votes channel.")
-                .await?;
-            return Ok(());
-        }
-    };
-    let night: GuildChannel = match get_channel_from_id(ctx, guild.id, cycle.night).await {
-        Ok(c) => c,
-        Err(_) => {
-            msg.channel_id
-                .say(&ctx.http, "I couldn't get the current night channel.")
-                .await?;
-            return Ok(());
-        }
-    };
+/// Lets the members of a channel read it, but not talk in it anymore.
+async fn lock_channel(
+    ctx: &Context,
+    channel: &GuildChannel,
+    bot_id: UserId,
+) -> serenity::Result<()> {
+    for overwrite in &channel.permission_overwrites {
+        if let PermissionOverwriteType::Member(id) = overwrite.kind {
+            if id == bot_id {
+                continue;
+            }
 
-    let role = match get_role(ctx, guild.id, data.player_role_id).await {
-        Ok(r) => r,
-        Err(_) => {
-            msg.channel_id
-                .say(&ctx.http, "Player role doesn't exist or is invalid now.")
-                .await?;
-            return Ok(());
+            let locked = PermissionOverwrite {
+                allow: Permissions::READ_MESSAGES | Permissions::READ_MESSAGE_HISTORY,
+                deny: Permissions::SEND_MESSAGES | Permissions::ADD_REACTIONS,
+                kind: overwrite.kind,
+            };
+            channel.create_permission(&ctx.http, &locked).await?;
         }
-    };
+    }
 
-    // Remove overwrites for `Player` role from day channels.
-    match day
-        .delete_permission(&ctx.http, PermissionOverwriteType::Role(role.id))
-        .await
-    {
-        Ok(_) => (),
-        Err(_) => {
-            msg.channel_id
-                .say(&ctx.http, "I couldn't change permissions for the channels.")
-                .await?;
-            return Ok(());
-        }
-    };
-    match votes
-        .delete_permission(&ctx.http, PermissionOverwriteType::Role(role.id))
-        .await
-    {
-        Ok(_) => (),
-        Err(_) => {
-            msg.channel_id
-                .say(
-                    &ctx.http,
-                    "I couldn't change permissions for the night channel.",
-                )
-                .await?;
-            return Ok(());
-        }
-    };
+    channel.say(&ctx.http, "This chat is now locked.").await?;
 
-    let overwrites = if let Some(cat_id) = night.category_id {
-        if let Some(category) = ctx.cache.category(cat_id).await {
-            category.permission_overwrites
-        } else {
-            Vec::new()
-        }
-    } else {
-        Vec::new()
-    };
+    Ok(())
+}
 
-    // Set overwrites for the night channel.
-    for overwrite in overwrites {
-        night.create_permission(&ctx.http, &overwrite).await?;
+/// Permissions given to the members of faction chats and hosts.
+const FACTION_CHAT_PERMS: Permissions = Permissions::from_bits_truncate(
+    Permissions::READ_MESSAGES.bits()
+        | Permissions::SEND_MESSAGES.bits()
+        | Permissions::ADD_REACTIONS.bits()
+        | Permissions::EMBED_LINKS.bits()
+        | Permissions::READ_MESSAGE_HISTORY.bits()
+        | Permissions::ATTACH_FILES.bits(),
+);
+
+/// Parses all remaining arguments as members.
+///
+/// Tells the user and returns `None` if one of them can't be found.
+async fn parse_members(
+    ctx: &Context,
+    msg: &Message,
+    guild_id: GuildId,
+    mut args: Args,
+) -> Result<Option<Vec<Member>>, CommandError> {
+    let mut members = Vec::new();
+    for arg in args.iter::<String>() {
+        let parsed_arg = match arg {
+            Ok(a) => a,
+            Err(_) => continue,
+        };
+        match get_member(ctx, guild_id, Some(&parsed_arg)).await {
+            Ok(m) => members.push(m),
+            Err(_) => {
+                msg.channel_id
+                    .say(&ctx.http, format!("No member found from {}.", &parsed_arg))
+                    .await?;
+                return Ok(None);
+            }
+        }
     }
 
-    msg.channel_id
-        .say(&ctx.http, format!("Night {} channel opened.", cycle.number))
-        .await?;
+    Ok(Some(members))
+}
 
-    // We'll handle night actions channel now.
-    // Errors with night action channel shouldn't affect opening and closing of night/day channels.
-    // First, clear list of users who have submitted NA.
-    sqlx::query!(
+/// Gets a faction chat of the game by its name, if it still exists.
+///
+/// Names are compared the way Discord names channels, so `Coven Chat` also
+/// finds `coven-chat`.
+async fn get_faction_chat(
+    ctx: &Context,
+    guild_id: GuildId,
+    pool: &PgPool,
+    name: &str,
+) -> Option<GuildChannel> {
+    let res = sqlx::query!(
         "
-        INSERT INTO config(
-            guild_id, na_submitted
-        ) VALUES (
-            $1, '{}'
-        ) ON CONFLICT (guild_id)
-        DO UPDATE SET na_submitted = '{}';
+        SELECT channel_id FROM faction_chats
+        WHERE guild_id = $1 AND lower(regexp_replace(trim(name), '\\s+', '-', 'g')) = $2;
         ",
-        guild.id.0 as i64
+        guild_id.0 as i64,
+        name.split_whitespace()
+            .collect::<Vec<_>>()
+            .join("-")
+            .to_lowercase()
     )
-    .execute(pool)
-    .await?;
-
-    // Now, fetch the channel or create it, and then send night beginning message.
-    let channel = match get_na_channel(&ctx, &guild, &pool).await {
-        Ok(c) => c,
-        Err(e) => return Err(CommandError::from(e)),
-    };
+    .fetch_all(pool)
+    .await
+    .ok()?;
 
-    match channel
-        .id
-        .say(
-            &ctx.http,
-            format!("**Night {} begins!**\n\n\n\n\u{200b}", cycle.number),
-        )
-        .await
-    {
-        Ok(_) => (),
-        Err(_) => {
-            msg.channel_id
-                .say(
-                    &ctx.http,
-                    "I couldn't send a message in the night actions channel.",
-                )
-                .await?;
+    for row in res {
+        if let Ok(c) = get_channel_from_id(ctx, guild_id, Some(row.channel_id)).await {
+            return Some(c);
         }
-    };
+    }
 
-    Ok(())
+    None
 }
 
-/// Returns night actions channel if it exists. If it doesn't, it creates
-/// a new channel, adds it to the database, and then returns it.
-pub(crate) async fn get_na_channel(
+/// Gets the game category, creating it if it doesn't exist.
+///
+/// Only hosts and the bot can see the category. Channels for the game, like
+/// faction chats, are created in it.
+pub(crate) async fn get_game_category(
     ctx: &Context,
     guild: &Guild,
-    pool: &sqlx::PgPool,
+    pool: &PgPool,
 ) -> Result<GuildChannel, &'static str> {
-    let na_channel_id = match sqlx::query!(
-        "SELECT na_channel_id FROM config WHERE guild_id = $1;",
+    let res = match sqlx::query!(
+        "SELECT game_category_id, host_role_id FROM config WHERE guild_id = $1;",
         guild.id.0 as i64
     )
     .fetch_one(pool)
     .await
     {
-        Ok(r) => r.na_channel_id,
-        Err(_) => return Err("Unable to fetch details of night actions channel from database."),
+        Ok(r) => r,
+        Err(_) => return Err("Unable to fetch details of the game category from database."),
     };
 
-    if let Ok(c) = get_channel_from_id(ctx, guild.id, na_channel_id).await {
-        return Ok(c);
+    if let Ok(c) = get_channel_from_id(ctx, guild.id, res.game_category_id).await {
+        if c.kind == ChannelType::Category {
+            return Ok(c);
+        }
     }
 
-    // Time to create the NA channel, add it to the database and then return it.
-    let perms = vec![
+    let mut perms = vec![
         PermissionOverwrite {
             allow: Permissions::empty(),
             deny: Permissions::READ_MESSAGES,
             kind: PermissionOverwriteType::Role(RoleId(guild.id.0)),
         },
         PermissionOverwrite {
-            allow: Permissions::READ_MESSAGES
-                | Permissions::SEND_MESSAGES
-                | Permissions::ADD_REACTIONS,
+            allow: Permissions::READ_MESSAGES | Permissions::SEND_MESSAGES,
             deny: Permissions::empty(),
             kind: PermissionOverwriteType::Member(ctx.cache.current_user_id().await),
         },
     ];
 
-    let channel = match guild
-        .create_channel(&ctx.http, |c| c.name("night-actions").permissions(perms))
+    if let Some(id) = res.host_role_id {
+        perms.push(PermissionOverwrite {
+            allow: Permissions::READ_MESSAGES | Permissions::SEND_MESSAGES,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Role(RoleId(id as u64)),
+        });
+    }
+
+    let category = match guild
+        .create_channel(&ctx.http, |c| {
+            c.name("Game")
+                .kind(ChannelType::Category)
+                .permissions(perms)
+        })
         .await
     {
         Ok(c) => c,
-        Err(_) => return Err("Unable to create a channel for night actions."),
+        Err(_) => return Err("Unable to create the game category."),
     };
 
     match sqlx::query!(
-        "
-        INSERT INTO config (
-            guild_id, na_channel_id
-        )
-        VALUES (
-            $1, $2
-        ) ON CONFLICT (guild_id)
-        DO UPDATE SET na_channel_id = $2;
-        ",
+        "UPDATE config SET game_category_id = $2 WHERE guild_id = $1;",
         guild.id.0 as i64,
-        channel.id.0 as i64
+        category.id.0 as i64
     )
     .execute(pool)
     .await
     {
         Ok(_) => (),
-        Err(_) => return Err("Unable to add newly created channel to database."),
+        Err(_) => return Err("Unable to add newly created category to database."),
     };
 
-    Ok(channel)
+    Ok(category)
 }
 
-/// Kills a player by removing player role and adding dead player role.
+/// Creates a category for a new cycle with day, votes and night channels.
 ///
-/// **Usage:** `[p]kill <user>`
+/// **Usage:** `[p]cycle [number]`
 ///
-/// The command fails if player and dead player roles are not set up.
-#[command("kill")]
-#[min_args(1)]
-async fn kill_player(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+/// If you use the bot to create all cycle channels, the bot will be able to detect
+/// the correct cycle number. If you didn't use the bot to create all cycles,
+/// please specify the cycle number in the command.
+///
+/// The category is named "Cycle x". The day, votes and night channels will be
+/// called "day-x", "day-x-voting" and "night-x" respectively.
+///
+/// Day and votes channels will be visible to everyone, and all Players will be able
+/// to write in it. Night channel will remain hidden.
+///
+/// *x is the cycle number*
+///
+/// The channels, their names and who can talk in them can be changed with the
+/// `tvm cycletemplate` command.
+///
+/// The bot asks for confirmation before creating the channels.
+#[command("cycle")]
+async fn create_cycle(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     let guild = match msg.guild(ctx).await {
         Some(i) => i,
         None => return Err(CommandError::from("Couldn't fetch details of this server.")),
     };
 
-    let input = args.message().to_string();
-    // Get tagged member.
-    let mut member = match get_member(ctx, guild.id, Some(&input)).await {
-        Ok(m) => m,
-        Err(_) => {
-            msg.channel_id
-                .say(&ctx.http, format!("No member found from `{}`.", input))
-                .await?;
-            return Ok(());
-        }
-    };
-
     let data_read = ctx.data.read().await;
     let pool = data_read.get::<ConnectionPool>().unwrap();
 
-    let res = match sqlx::query!(
-        "SELECT dead_role_id, player_role_id FROM config WHERE guild_id = $1;",
+    let data = match sqlx::query_as_unchecked!(
+        Data,
+        "SELECT player_role_id, cycle FROM config WHERE guild_id = $1",
         guild.id.0 as i64
     )
     .fetch_one(pool)
     .await
     {
-        Ok(r) => r,
+        Ok(d) => d,
         Err(_) => {
             return Err(CommandError::from(
-                "Unable to fetch details of set roles from database.",
+                "Couldn't fetch details from the database.",
             ))
         }
     };
 
-    let player_role = match get_role(&ctx, guild.id, res.player_role_id).await {
-        Ok(r) => r,
-        Err(_) => {
-            msg.channel_id
-                .say(&ctx.http, "I couldn't find the player role.")
-                .await?;
-            return Ok(());
-        }
+    let cycle = match data.cycle {
+        Some(c) => c.0,
+        None => Cycle::default(),
     };
 
-    let dead_role = match get_role(&ctx, guild.id, res.dead_role_id).await {
-        Ok(r) => r,
-        Err(_) => {
-            msg.channel_id
-                .say(&ctx.http, "I couldn't find the dead player role.")
-                .await?;
-            return Ok(());
+    // Parse args to check if user supplied a cycle number.
+    // If not, use number in database after adding one.
+    let number: i16 = match args.message().parse() {
+        Ok(n) => n,
+        Err(_) => cycle.number + 1,
+    };
+
+    // Confirmation for cycle creation.
+    let confirm_msg = msg
+        .channel_id
+        .say(
+            &ctx.http,
+            format!(
+                "
+                Are you sure you want to create cycle `{}` channels? Make \
+                sure you have the day text ready. Users will be able to talk \
+                in the day and vote channels as soon as they are created.
+                ",
+                number
+            )
+            .trim(),
+        )
+        .await?;
+
+    if !yes_or_no_prompt(&ctx, &confirm_msg, &msg.author, 30.0).await? {
+        msg.channel_id
+            .say(&ctx.http, "Cancelled cycle creation.")
+            .await?;
+        return Ok(());
+    }
+
+    // User confirmed. Let's do it.
+    let template = CycleTemplate::get(pool, guild.id).await;
+    let roles = GameRoles::get(pool, guild.id).await?;
+    if let Some(role) = template.missing_role(&roles, &guild) {
+        msg.channel_id
+            .say(
+                &ctx.http,
+                format!(
+                    "The `{}` role used by the cycle template doesn't exist or is invalid now.",
+                    role.name()
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let dead_view = get_dead_view_role(pool, guild.id).await;
+    let night_position = template.position(ChannelUse::Night);
+
+    let me = ctx.cache.current_user().await;
+    let default_role = RoleId(guild.id.0);
+
+    let perms = vec![
+        PermissionOverwrite {
+            allow: Permissions::READ_MESSAGES | Permissions::ADD_REACTIONS,
+            deny: Permissions::SEND_MESSAGES,
+            kind: PermissionOverwriteType::Role(default_role),
+        },
+        PermissionOverwrite {
+            allow: Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Member(me.id),
+        },
+    ];
+
+    let mut transaction = Transaction::new();
+
+    let category = match guild
+        .create_channel(&ctx.http, |c| {
+            c.name(fill(&template.category, number))
+                .kind(ChannelType::Category)
+                .permissions(perms)
+        })
+        .await
+    {
+        Ok(c) => c,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, "I'm unable to create a category.")
+                .await?;
+            return Ok(());
+        }
+    };
+    transaction.channel(&category);
+
+    let mut channels = Vec::new();
+    for (i, channel) in template.channels.iter().enumerate() {
+        let name = fill(&channel.name, number);
+        let mut overwrites = channel.overwrites(Phase::Day, &roles, guild.id, me.id);
+        if night_position == Some(i) {
+            add_dead_view(&mut overwrites, dead_view);
+        }
+
+        let res = guild
+            .create_channel(&ctx.http, |c| {
+                c.name(&name)
+                    .kind(ChannelType::Text)
+                    .category(&category)
+                    .permissions(overwrites)
+                    .rate_limit(channel.slowmode);
+                if let Some(topic) = &channel.topic {
+                    c.topic(fill(topic, number));
+                }
+                c
+            })
+            .await;
+
+        match res {
+            Ok(c) => {
+                transaction.channel(&c);
+                channels.push(c.id.0 as i64);
+            }
+            Err(_) => {
+                let report = transaction.rollback(ctx).await;
+                msg.channel_id
+                    .say(
+                        &ctx.http,
+                        format!("I couldn't create the `{}` channel. {}", name, report),
+                    )
+                    .await?;
+                return Ok(());
+            }
+        }
+    }
+    let channel_for = |channel_use| template.position(channel_use).map(|i| channels[i]);
+
+    // Time to update the database.
+    let res = sqlx::query!(
+        r#"
+        INSERT INTO config(
+            guild_id,
+            cycle,
+            na_submitted
+        ) VALUES (
+            $1,
+            $2,
+            null
+        ) ON CONFLICT (guild_id)
+        DO UPDATE SET
+            cycle = $2,
+            na_submitted = null;
+        "#,
+        guild.id.0 as i64,
+        serde_json::to_value(Cycle {
+            number: number,
+            day: channel_for(ChannelUse::Day),
+            night: channel_for(ChannelUse::Night),
+            votes: channel_for(ChannelUse::Votes),
+            channels: channels.clone(),
+            night_started: false,
+            template: Some(template.clone())
+        })
+        .unwrap()
+    )
+    .execute(pool)
+    .await;
+
+    if res.is_err() {
+        let report = transaction.rollback(ctx).await;
+        msg.channel_id
+            .say(
+                &ctx.http,
+                format!("I couldn't save the new cycle in the database. {}", report),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    end_links(ctx, guild.id, pool, true).await;
+    close_temp_chats(ctx, guild.id, pool, true).await;
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!("Created cycle `{}` category and channels!", number),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Closes the day channels and opens the night channel.
+///
+/// **Usage:** `[p]night`
+///
+/// Day and votes channels will remain visible to everyone but Players won't be
+/// able to write in them. Night channel will become visible to everyone, and Players
+/// will be able to write in it. If the server has a cycle template, the channels
+/// are changed as the template says instead.
+///
+/// If the Night Actions channel is set up, the bot will send a message marking the
+/// beginning of night x, where x is the cycle number. If Night Actions channel is not
+/// set up, the bot will create a new channel called "night-actions" and send the message
+/// in it. The channel will be visible by hosts and the bot only.
+///
+/// The bot asks for confirmation before executing the command.
+#[command]
+async fn night(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let data = match sqlx::query_as_unchecked!(
+        Data,
+        "SELECT player_role_id, cycle FROM config WHERE guild_id = $1",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(d) => d,
+        Err(_) => {
+            return Err(CommandError::from(
+                "Couldn't fetch details from the database.",
+            ))
+        }
+    };
+
+    let cycle: Cycle = match data.cycle {
+        Some(c) => c.0,
+        None => {
+            return Err(CommandError::from(
+                "I couldn't get cycle details in the database.",
+            ))
+        }
+    };
+
+    // Confirmation for cycle creation.
+    let confirm_msg = msg
+        .channel_id
+        .say(
+            &ctx.http,
+            format!(
+                "
+                Are you sure you want to start night `{}`? Make \
+                sure you have already posted the night-starting text. \
+                Users will be able to talk in the night channel as soon \
+                as the channel is opened.
+                ",
+                cycle.number
+            )
+            .trim(),
+        )
+        .await?;
+
+    if !yes_or_no_prompt(&ctx, &confirm_msg, &msg.author, 30.0).await? {
+        msg.channel_id
+            .say(&ctx.http, "Cancelled starting of night.")
+            .await?;
+        return Ok(());
+    }
+
+    // Cycles created before templates existed used the default template.
+    let (template, channel_ids) = if cycle.channels.is_empty() {
+        let ids = vec![cycle.day, cycle.votes, cycle.night];
+        (CycleTemplate::default(), ids)
+    } else {
+        let ids = cycle.channels.iter().map(|i| Some(*i)).collect();
+        (get_cycle_template(pool, guild.id, &cycle).await, ids)
+    };
+
+    if template.channels.len() != channel_ids.len() {
+        msg.channel_id
+            .say(
+                &ctx.http,
+                "The cycle template was changed after this cycle was created. \
+                Please change the channels yourself.",
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let roles = GameRoles::get(pool, guild.id).await?;
+    if let Some(role) = template.missing_role(&roles, &guild) {
+        msg.channel_id
+            .say(
+                &ctx.http,
+                format!(
+                    "The `{}` role used by the cycle template doesn't exist or is invalid now.",
+                    role.name()
+                ),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let dead_view = get_dead_view_role(pool, guild.id).await;
+    let night_position = template.position(ChannelUse::Night);
+
+    let me = ctx.cache.current_user_id().await;
+    let mut transaction = Transaction::new();
+
+    for (i, (template_channel, id)) in template.channels.iter().zip(channel_ids).enumerate() {
+        let channel = match get_channel_from_id(ctx, guild.id, id).await {
+            Ok(c) => c,
+            Err(_) => {
+                let report = transaction.rollback(ctx).await;
+                msg.channel_id
+                    .say(
+                        &ctx.http,
+                        format!(
+                            "I couldn't get the current `{}` channel. {}",
+                            fill(&template_channel.name, cycle.number),
+                            report
+                        ),
+                    )
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let mut day_overwrites = template_channel.overwrites(Phase::Day, &roles, guild.id, me);
+        let mut night_overwrites = template_channel.overwrites(Phase::Night, &roles, guild.id, me);
+        if night_position == Some(i) {
+            add_dead_view(&mut day_overwrites, dead_view);
+            add_dead_view(&mut night_overwrites, dead_view);
+        }
+
+        // Overwrites only needed during the day are removed.
+        for overwrite in day_overwrites
+            .iter()
+            .filter(|d| !night_overwrites.iter().any(|n| n.kind == d.kind))
+        {
+            transaction.overwrite(&channel, overwrite.kind);
+            if channel
+                .delete_permission(&ctx.http, overwrite.kind)
+                .await
+                .is_err()
+            {
+                let report = transaction.rollback(ctx).await;
+                msg.channel_id
+                    .say(
+                        &ctx.http,
+                        format!("I couldn't change permissions for the channels. {}", report),
+                    )
+                    .await?;
+                return Ok(());
+            }
+        }
+
+        for overwrite in &night_overwrites {
+            transaction.overwrite(&channel, overwrite.kind);
+            if channel
+                .create_permission(&ctx.http, overwrite)
+                .await
+                .is_err()
+            {
+                let report = transaction.rollback(ctx).await;
+                msg.channel_id
+                    .say(
+                        &ctx.http,
+                        format!("I couldn't change permissions for the channels. {}", report),
+                    )
+                    .await?;
+                return Ok(());
+            }
+        }
+    }
+
+    sqlx::query!(
+        "UPDATE config SET cycle = jsonb_set(cycle, '{night_started}', 'true') WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .execute(pool)
+    .await?;
+
+    end_links(ctx, guild.id, pool, false).await;
+    close_temp_chats(ctx, guild.id, pool, false).await;
+
+    msg.channel_id
+        .say(&ctx.http, format!("Night {} channel opened.", cycle.number))
+        .await?;
+
+    // We'll handle night actions channel now.
+    // Errors with night action channel shouldn't affect opening and closing of night/day channels.
+    // First, clear list of users who have submitted NA.
+    sqlx::query!(
+        "
+        INSERT INTO config(
+            guild_id, na_submitted
+        ) VALUES (
+            $1, '{}'
+        ) ON CONFLICT (guild_id)
+        DO UPDATE SET na_submitted = '{}';
+        ",
+        guild.id.0 as i64
+    )
+    .execute(pool)
+    .await?;
+
+    // Now, fetch the channel or create it, and then send night beginning message.
+    let channel = match get_na_channel(&ctx, &guild, &pool).await {
+        Ok(c) => c,
+        Err(e) => return Err(CommandError::from(e)),
+    };
+
+    match channel
+        .id
+        .say(
+            &ctx.http,
+            format!("**Night {} begins!**\n\n\n\n\u{200b}", cycle.number),
+        )
+        .await
+    {
+        Ok(_) => (),
+        Err(_) => {
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    "I couldn't send a message in the night actions channel.",
+                )
+                .await?;
+        }
+    };
+
+    Ok(())
+}
+
+/// Returns night actions channel if it exists. If it doesn't, it creates
+/// a new channel, adds it to the database, and then returns it.
+pub(crate) async fn get_na_channel(
+    ctx: &Context,
+    guild: &Guild,
+    pool: &sqlx::PgPool,
+) -> Result<GuildChannel, &'static str> {
+    let na_channel_id = match sqlx::query!(
+        "SELECT na_channel_id FROM config WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(r) => r.na_channel_id,
+        Err(_) => return Err("Unable to fetch details of night actions channel from database."),
+    };
+
+    if let Ok(c) = get_channel_from_id(ctx, guild.id, na_channel_id).await {
+        return Ok(c);
+    }
+
+    // Time to create the NA channel, add it to the database and then return it.
+    let perms = vec![
+        PermissionOverwrite {
+            allow: Permissions::empty(),
+            deny: Permissions::READ_MESSAGES,
+            kind: PermissionOverwriteType::Role(RoleId(guild.id.0)),
+        },
+        PermissionOverwrite {
+            allow: Permissions::READ_MESSAGES
+                | Permissions::SEND_MESSAGES
+                | Permissions::ADD_REACTIONS,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Member(ctx.cache.current_user_id().await),
+        },
+    ];
+
+    let channel = match guild
+        .create_channel(&ctx.http, |c| c.name("night-actions").permissions(perms))
+        .await
+    {
+        Ok(c) => c,
+        Err(_) => return Err("Unable to create a channel for night actions."),
+    };
+
+    match sqlx::query!(
+        "
+        INSERT INTO config (
+            guild_id, na_channel_id
+        )
+        VALUES (
+            $1, $2
+        ) ON CONFLICT (guild_id)
+        DO UPDATE SET na_channel_id = $2;
+        ",
+        guild.id.0 as i64,
+        channel.id.0 as i64
+    )
+    .execute(pool)
+    .await
+    {
+        Ok(_) => (),
+        Err(_) => return Err("Unable to add newly created channel to database."),
+    };
+
+    Ok(channel)
+}
+
+/// Gets the whisper log channel, creating it if it doesn't exist.
+///
+/// Only hosts and the bot can see the channel.
+pub(crate) async fn get_whisper_log(
+    ctx: &Context,
+    guild: &Guild,
+    pool: &sqlx::PgPool,
+) -> Result<GuildChannel, &'static str> {
+    let res = match sqlx::query!(
+        "SELECT whisper_log_id, host_role_id FROM config WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(r) => r,
+        Err(_) => return Err("Unable to fetch details of whisper log channel from database."),
+    };
+
+    if let Ok(c) = get_channel_from_id(ctx, guild.id, res.whisper_log_id).await {
+        return Ok(c);
+    }
+
+    let mut perms = vec![
+        PermissionOverwrite {
+            allow: Permissions::empty(),
+            deny: Permissions::READ_MESSAGES,
+            kind: PermissionOverwriteType::Role(RoleId(guild.id.0)),
+        },
+        PermissionOverwrite {
+            allow: Permissions::READ_MESSAGES
+                | Permissions::SEND_MESSAGES
+                | Permissions::ADD_REACTIONS,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Member(ctx.cache.current_user_id().await),
+        },
+    ];
+
+    if let Some(id) = res.host_role_id {
+        perms.push(PermissionOverwrite {
+            allow: Permissions::READ_MESSAGES | Permissions::READ_MESSAGE_HISTORY,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Role(RoleId(id as u64)),
+        });
+    }
+
+    let channel = match guild
+        .create_channel(&ctx.http, |c| c.name("whispers").permissions(perms))
+        .await
+    {
+        Ok(c) => c,
+        Err(_) => return Err("Unable to create a channel for whispers."),
+    };
+
+    match sqlx::query!(
+        "UPDATE config SET whisper_log_id = $2 WHERE guild_id = $1;",
+        guild.id.0 as i64,
+        channel.id.0 as i64
+    )
+    .execute(pool)
+    .await
+    {
+        Ok(_) => (),
+        Err(_) => return Err("Unable to add newly created channel to database."),
+    };
+
+    Ok(channel)
+}
+
+/// Kills a player by removing player role and adding dead player role.
+///
+/// **Usage:** `[p]kill <user>`
+///
+/// The command fails if player and dead player roles are not set up.
+#[command("kill")]
+#[min_args(1)]
+async fn kill_player(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let input = args.message().to_string();
+    // Get tagged member.
+    let mut member = match get_member(ctx, guild.id, Some(&input)).await {
+        Ok(m) => m,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, format!("No member found from `{}`.", input))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let res = match sqlx::query!(
+        "SELECT dead_role_id, player_role_id, dead_see_chats FROM config WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(r) => r,
+        Err(_) => {
+            return Err(CommandError::from(
+                "Unable to fetch details of set roles from database.",
+            ))
+        }
+    };
+
+    let player_role = match get_role(&ctx, guild.id, res.player_role_id).await {
+        Ok(r) => r,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, "I couldn't find the player role.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let dead_role = match get_role(&ctx, guild.id, res.dead_role_id).await {
+        Ok(r) => r,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, "I couldn't find the dead player role.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if !member.roles.contains(&player_role.id) {
+        msg.channel_id
+            .say(&ctx.http, "User doesn't have the player role!")
+            .await?;
+        return Ok(());
+    }
+
+    // Remove player role.
+    if member.remove_role(&ctx.http, player_role.id).await.is_err() {
+        msg.channel_id
+            .say(&ctx.http, "I couldn't remove player role from the user.")
+            .await?;
+        return Ok(());
+    }
+
+    // Add dead player role.
+    if member.add_role(&ctx.http, dead_role.id).await.is_err() {
+        msg.channel_id
+            .say(
+                &ctx.http,
+                "I couldn't add the dead player role to the user.",
+            )
+            .await?;
+        return Ok(());
+    }
+
+    // The dead chat is created on the first kill.
+    let dead_chat = get_dead_chat(ctx, &guild, pool).await;
+
+    if res.dead_see_chats {
+        if let Err(e) = grant_dead_view(ctx, &guild, pool, member.user.id).await {
+            msg.channel_id.say(&ctx.http, e).await?;
+        }
+    }
+
+    let reply = match dead_chat {
+        Ok(c) => format!(
+            "Removed player role and added dead player role to the user! They can now talk in {}.",
+            c.mention()
+        ),
+        Err(e) => format!(
+            "Removed player role and added dead player role to the user! {}",
+            e
+        ),
+    };
+    msg.channel_id.say(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+/// Brings a dead player back to life.
+///
+/// **Usage:** `[p]revive <user>`
+///
+/// Removes the dead player role and adds the player role back. If dead
+/// players could see night and faction chats, the user loses that view.
+#[command("revive")]
+#[min_args(1)]
+async fn revive_player(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let input = args.message().to_string();
+    let mut member = match get_member(ctx, guild.id, Some(&input)).await {
+        Ok(m) => m,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, format!("No member found from `{}`.", input))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let res = sqlx::query!(
+        "SELECT dead_role_id, player_role_id FROM config WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let player_role = match get_role(ctx, guild.id, res.player_role_id).await {
+        Ok(r) => r,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, "I couldn't find the player role.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let dead_role = match get_role(ctx, guild.id, res.dead_role_id).await {
+        Ok(r) => r,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, "I couldn't find the dead player role.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if !member.roles.contains(&dead_role.id) {
+        msg.channel_id
+            .say(&ctx.http, "User doesn't have the dead player role!")
+            .await?;
+        return Ok(());
+    }
+
+    if member.remove_role(&ctx.http, dead_role.id).await.is_err() {
+        msg.channel_id
+            .say(
+                &ctx.http,
+                "I couldn't remove dead player role from the user.",
+            )
+            .await?;
+        return Ok(());
+    }
+
+    if member.add_role(&ctx.http, player_role.id).await.is_err() {
+        msg.channel_id
+            .say(&ctx.http, "I couldn't add the player role to the user.")
+            .await?;
+        return Ok(());
+    }
+
+    if let Err(e) = revoke_dead_view(ctx, &guild, pool, member.user.id).await {
+        msg.channel_id.say(&ctx.http, e).await?;
+    }
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            "Removed dead player role and added player role to the user!",
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Gets the dead chat, creating it if it doesn't exist.
+///
+/// Dead players, spectators and hosts can see and talk in it.
+async fn get_dead_chat(
+    ctx: &Context,
+    guild: &Guild,
+    pool: &PgPool,
+) -> Result<GuildChannel, &'static str> {
+    let res = match sqlx::query!(
+        "SELECT dead_chat_id, dead_role_id, spec_role_id, host_role_id FROM config WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(r) => r,
+        Err(_) => return Err("Unable to fetch details of the dead chat from database."),
+    };
+
+    if let Ok(c) = get_channel_from_id(ctx, guild.id, res.dead_chat_id).await {
+        return Ok(c);
+    }
+
+    let category = get_game_category(ctx, guild, pool).await?;
+
+    let mut perms = vec![
+        PermissionOverwrite {
+            allow: Permissions::empty(),
+            deny: Permissions::READ_MESSAGES,
+            kind: PermissionOverwriteType::Role(RoleId(guild.id.0)),
+        },
+        PermissionOverwrite {
+            allow: Permissions::READ_MESSAGES | Permissions::SEND_MESSAGES,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Member(ctx.cache.current_user_id().await),
+        },
+    ];
+
+    for id in [res.dead_role_id, res.spec_role_id, res.host_role_id]
+        .iter()
+        .flatten()
+    {
+        perms.push(PermissionOverwrite {
+            allow: FACTION_CHAT_PERMS,
+            deny: Permissions::empty(),
+            kind: PermissionOverwriteType::Role(RoleId(*id as u64)),
+        });
+    }
+
+    let channel = match guild
+        .create_channel(&ctx.http, |c| {
+            c.name("dead-chat")
+                .kind(ChannelType::Text)
+                .permissions(perms)
+                .category(&category)
+        })
+        .await
+    {
+        Ok(c) => c,
+        Err(_) => return Err("Unable to create the dead chat."),
+    };
+
+    match sqlx::query!(
+        "UPDATE config SET dead_chat_id = $2 WHERE guild_id = $1;",
+        guild.id.0 as i64,
+        channel.id.0 as i64
+    )
+    .execute(pool)
+    .await
+    {
+        Ok(_) => (),
+        Err(_) => return Err("Unable to add newly created channel to database."),
+    };
+
+    Ok(channel)
+}
+
+/// Lets a dead player read the current night channel and all faction chats.
+///
+/// The player's own overwrites are saved, so they can be restored on revive.
+async fn grant_dead_view(
+    ctx: &Context,
+    guild: &Guild,
+    pool: &PgPool,
+    user_id: UserId,
+) -> Result<(), &'static str> {
+    let channels = match get_night_and_faction_chats(ctx, guild, pool).await {
+        Ok(c) => c,
+        Err(_) => return Err("I couldn't fetch the night and faction chats."),
+    };
+
+    let kind = PermissionOverwriteType::Member(user_id);
+    for channel in channels {
+        let previous = channel
+            .permission_overwrites
+            .iter()
+            .find(|p| p.kind == kind);
+
+        let res = sqlx::query!(
+            "
+            INSERT INTO dead_views(guild_id, user_id, channel_id, previous_allow, previous_deny)
+            VALUES($1, $2, $3, $4, $5)
+            ON CONFLICT (guild_id, user_id, channel_id) DO NOTHING;
+            ",
+            guild.id.0 as i64,
+            user_id.0 as i64,
+            channel.id.0 as i64,
+            previous.map(|p| p.allow.bits() as i64),
+            previous.map(|p| p.deny.bits() as i64)
+        )
+        .execute(pool)
+        .await;
+        if res.is_err() {
+            return Err("I couldn't save the permissions of the dead player.");
+        }
+
+        let overwrite = PermissionOverwrite {
+            allow: Permissions::READ_MESSAGES | Permissions::READ_MESSAGE_HISTORY,
+            deny: Permissions::SEND_MESSAGES | Permissions::ADD_REACTIONS,
+            kind,
+        };
+        if channel
+            .create_permission(&ctx.http, &overwrite)
+            .await
+            .is_err()
+        {
+            return Err("I couldn't let the dead player see the night and faction chats.");
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets the template a cycle was created with.
+async fn get_cycle_template(pool: &PgPool, guild_id: GuildId, cycle: &Cycle) -> CycleTemplate {
+    match &cycle.template {
+        Some(t) => t.clone(),
+        // Cycles created before templates were saved with them use the current one.
+        None => CycleTemplate::get(pool, guild_id).await,
+    }
+}
+
+/// Gets the dead player role if dead players can see night and faction chats.
+async fn get_dead_view_role(pool: &PgPool, guild_id: GuildId) -> Option<RoleId> {
+    let res = sqlx::query!(
+        "SELECT dead_role_id, dead_see_chats FROM config WHERE guild_id = $1;",
+        guild_id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await;
+
+    match res {
+        Ok(Some(r)) if r.dead_see_chats => r.dead_role_id.map(|i| RoleId(i as u64)),
+        _ => None,
+    }
+}
+
+/// Lets the dead player role read a channel, but not talk in it.
+fn dead_view_overwrite(role_id: RoleId) -> PermissionOverwrite {
+    PermissionOverwrite {
+        allow: Permissions::READ_MESSAGES | Permissions::READ_MESSAGE_HISTORY,
+        deny: Permissions::SEND_MESSAGES | Permissions::ADD_REACTIONS,
+        kind: PermissionOverwriteType::Role(role_id),
+    }
+}
+
+/// Adds the view of the dead player role to overwrites, unless the role
+/// already has an overwrite from the cycle template.
+fn add_dead_view(overwrites: &mut Vec<PermissionOverwrite>, dead_role: Option<RoleId>) {
+    if let Some(id) = dead_role {
+        let kind = PermissionOverwriteType::Role(id);
+        if !overwrites.iter().any(|o| o.kind == kind) {
+            overwrites.push(dead_view_overwrite(id));
+        }
+    }
+}
+
+/// Restores the overwrites a player had before `grant_dead_view`.
+async fn revoke_dead_view(
+    ctx: &Context,
+    guild: &Guild,
+    pool: &PgPool,
+    user_id: UserId,
+) -> Result<(), &'static str> {
+    let views = match sqlx::query!(
+        "
+        DELETE FROM dead_views WHERE guild_id = $1 AND user_id = $2
+        RETURNING channel_id, previous_allow, previous_deny;
+        ",
+        guild.id.0 as i64,
+        user_id.0 as i64
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(v) => v,
+        Err(_) => return Err("I couldn't fetch the channels the dead player could see."),
+    };
+
+    let kind = PermissionOverwriteType::Member(user_id);
+    for view in views {
+        let channel_id = ChannelId(view.channel_id as u64);
+        let res = match (view.previous_allow, view.previous_deny) {
+            (Some(allow), Some(deny)) => {
+                let overwrite = PermissionOverwrite {
+                    allow: Permissions::from_bits_truncate(allow as u64),
+                    deny: Permissions::from_bits_truncate(deny as u64),
+                    kind,
+                };
+                channel_id.create_permission(&ctx.http, &overwrite).await
+            }
+            _ => channel_id.delete_permission(&ctx.http, kind).await,
+        };
+
+        if res.is_err() {
+            return Err("I couldn't restore the permissions of the player in all channels.");
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets the night channel of the current cycle and all faction chats.
+async fn get_night_and_faction_chats(
+    ctx: &Context,
+    guild: &Guild,
+    pool: &PgPool,
+) -> Result<Vec<GuildChannel>, sqlx::Error> {
+    let mut channels = Vec::new();
+
+    let cycle = sqlx::query_as_unchecked!(
+        CycleContainer,
+        "SELECT cycle FROM config WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await?;
+
+    if let Some(c) = cycle.cycle {
+        if let Ok(channel) = get_channel_from_id(ctx, guild.id, c.night).await {
+            channels.push(channel);
+        }
+    }
+
+    let chats = sqlx::query!(
+        "SELECT channel_id FROM faction_chats WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for chat in chats {
+        if let Ok(channel) = get_channel_from_id(ctx, guild.id, Some(chat.channel_id)).await {
+            channels.push(channel);
+        }
+    }
+
+    Ok(channels)
+}
+
+/// Generates player list and sends it in the specified channel.
+///
+/// **Usage:** `[p]playerlist <channel>`
+///
+/// **Aliases:** `plist`, `pl`
+///
+/// The player role must be set for this command to work.
+///
+/// I need the permission to embed links in the specified channel.
+#[command("playerlist")]
+#[aliases("plist", "pl")]
+#[min_args(1)]
+async fn player_list(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    // Get argument channel.
+    let input = args.message().to_string();
+    let channel = match get_channel(&ctx, guild.id, Some(&input)).await {
+        Ok(c) => c,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, format!("No channel found from `{}`.", input))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let res = match sqlx::query!(
+        "SELECT player_role_id FROM config WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(i) => i,
+        Err(_) => {
+            return Err(CommandError::from(
+                "Couldn't fetch details from the database.",
+            ))
+        }
+    };
+
+    let role = match get_role(ctx, guild.id, res.player_role_id).await {
+        Ok(r) => r,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, "Player role has not been set up.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let players: Vec<_> = guild
+        .members
+        .values()
+        .filter_map(|m| {
+            if m.roles.contains(&role.id) {
+                Some(m.mention())
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    if players.is_empty() {
+        msg.channel_id.say(&ctx.http, "No players!").await?;
+        return Ok(());
+    }
+
+    let mut players_str = String::new();
+    for (i, player) in players.iter().enumerate() {
+        let _ = write!(players_str, "\n{}. {}", i + 1, player);
+    }
+
+    let sent = channel
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.title("Player List");
+                e.description(players_str);
+                e.colour(role.colour);
+                e.footer(|f| {
+                    f.text(format!("Total Players: {0}", players.len()));
+
+                    f
+                });
+
+                e
+            });
+
+            m
+        })
+        .await;
+
+    if sent.is_err() {
+        msg.channel_id.say(
+            &ctx.http,
+            format!(
+                "I couldn't send the player list. Please check if I have permissions to embed link in {}.",
+                channel.mention()
+            )
+        ).await?;
+    }
+
+    Ok(())
+}
+
+/// Shows information about the current cycle.
+///
+/// **Usage:** `[p]current`
+///
+/// I need the permission to embed links in the specified channel.
+#[command]
+async fn current(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let cycle: Cycle = match sqlx::query_as_unchecked!(
+        CycleContainer,
+        "SELECT cycle FROM config WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(cf) => {
+            if let Some(c) = cf.cycle {
+                c.0
+            } else {
+                Cycle::default()
+            }
+        }
+        Err(_) => {
+            return Err(CommandError::from(
+                "Couldn't fetch details from the database.",
+            ))
+        }
+    };
+
+    let day = match cycle.day {
+        Some(i) => format!("<#{}>", i),
+        None => String::from("Not set"),
+    };
+    let voting = match cycle.votes {
+        Some(i) => format!("<#{}>", i),
+        None => String::from("Not set"),
+    };
+    let night = match cycle.night {
+        Some(i) => format!("<#{}>", i),
+        None => String::from("Not set"),
+    };
+
+    let mut description = format!(
+        "**Day:** {}\n**Voting:** {}\n**Night:** {}",
+        day, voting, night
+    );
+
+    if !cycle.channels.is_empty() {
+        let phase = if cycle.night_started { "Night" } else { "Day" };
+        write!(description, "\n**Phase:** {}", phase)?;
+
+        // List the channels of the template that aren't used for anything.
+        let template = get_cycle_template(pool, guild.id, &cycle).await;
+        if template.channels.len() == cycle.channels.len() {
+            for (channel, id) in template.channels.iter().zip(&cycle.channels) {
+                if channel.uses.is_empty() {
+                    write!(description, "\n**Other:** <#{}>", id)?;
+                }
+            }
+        }
+    }
+
+    let sent = msg
+        .channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(EMBED_COLOUR);
+                e.title(format!("Cycle Number {}", cycle.number));
+                e.description(description);
+
+                e
+            });
+
+            m
+        })
+        .await;
+
+    if sent.is_err() {
+        msg.channel_id
+            .say(&ctx.http, "I couldn't send an embed.")
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Announces a message in an embed.
+///
+/// **Usage:** `[p]announce [channel] <msg>`
+///
+/// You can optionally specify a channel by its ID or mention to send the
+/// embed announcement in that channel. The actual `msg` can contain hyperlinks
+/// in addition to normal Discord Markdown formatting.
+///
+/// I need the permission to embed links in the specified channel.
+#[command]
+#[min_args(1)]
+async fn announce(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let arg = args.current().unwrap();
+    let channel_id = if let Some(c) =
+        GuildChannel::from_guild_id_and_str(ctx, msg.guild_id.unwrap(), arg).await
+    {
+        args.advance();
+
+        c.id
+    } else {
+        msg.channel_id
+    };
+
+    if args.rest().is_empty() {
+        msg.channel_id
+            .say(&ctx.http, "Expected message to announce!")
+            .await?;
+    }
+
+    channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.description(args.rest())
+                    .timestamp(Utc::now().to_rfc3339())
+                    .colour(EMBED_COLOUR)
+            })
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Bans a user from signing up for games.
+///
+/// **Usage:** `[p]gameban <user> [days] [reason]`
+///
+/// The ban is permanent if you don't supply the number of days. A reason
+/// can't start with a number, as it would be taken as the days. Users who
+/// are banned can't sign-up as players or replacements. They are told the
+/// reason and when the ban ends if they try.
+///
+/// **Example**
+///
+/// Command: `[p]gameban Arius 14 Leaving games midway`
+/// Result: Arius can't sign-up for the next 14 days.
+#[command("gameban")]
+#[min_args(1)]
+async fn game_ban(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let input = args.single_quoted::<String>()?;
+    let member = match get_member(ctx, guild.id, Some(&input)).await {
+        Ok(m) => m,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, format!("No member found from `{}`.", input))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    // Anything that looks like a number is taken as `days`, so a typo can't
+    // turn into a permanent ban.
+    let days = args.current().map(str::to_string).filter(|a| {
+        let digits = a.strip_prefix('-').unwrap_or(a);
+        !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+    });
+
+    let expires_at = match days {
+        Some(days) => {
+            args.advance();
+            if days.starts_with('-') || days.trim_start_matches('0').is_empty() {
+                msg.channel_id
+                    .say(&ctx.http, "`days` must be a positive number.")
+                    .await?;
+                return Ok(());
+            }
+
+            match days
+                .parse::<u32>()
+                .ok()
+                .and_then(|d| Utc::now().checked_add_signed(chrono::Duration::days(d as i64)))
+            {
+                Some(t) => Some(t),
+                None => {
+                    msg.channel_id
+                        .say(
+                            &ctx.http,
+                            "That's too many days. Leave `days` out for a permanent ban.",
+                        )
+                        .await?;
+                    return Ok(());
+                }
+            }
+        }
+        None => None,
+    };
+
+    let reason = args.remains().map(|r| r.to_string());
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    sqlx::query!(
+        "
+        INSERT INTO game_bans(guild_id, user_id, reason, banned_by, expires_at)
+        VALUES($1, $2, $3, $4, $5)
+        ON CONFLICT (guild_id, user_id) DO UPDATE
+        SET reason = $3, banned_by = $4, banned_at = now(), expires_at = $5;
+        ",
+        guild.id.0 as i64,
+        member.user.id.0 as i64,
+        reason,
+        msg.author.id.0 as i64,
+        expires_at
+    )
+    .execute(pool)
+    .await?;
+
+    let until = match expires_at {
+        Some(t) => format!("until {}", t.format("%B %-d, %Y at %H:%M UTC")),
+        None => String::from("permanently"),
+    };
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!(
+                "Banned **{}** from signing up for games {}.",
+                member.user.tag(),
+                until
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Lifts a user's game ban.
+///
+/// **Usage:** `[p]gameunban <user>`
+///
+/// You can use the user's ID if they are no longer in the server.
+#[command("gameunban")]
+#[min_args(1)]
+async fn game_unban(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild_id = match msg.guild_id {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let input = args.message().to_string();
+    let user_id = match get_member(ctx, guild_id, Some(&input)).await {
+        Ok(m) => m.user.id,
+        Err(_) => match input.parse::<u64>() {
+            Ok(i) => UserId(i),
+            Err(_) => {
+                msg.channel_id
+                    .say(&ctx.http, format!("No member found from `{}`.", input))
+                    .await?;
+                return Ok(());
+            }
+        },
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let res = sqlx::query!(
+        "DELETE FROM game_bans WHERE guild_id = $1 AND user_id = $2;",
+        guild_id.0 as i64,
+        user_id.0 as i64
+    )
+    .execute(pool)
+    .await?;
+
+    if res.rows_affected() == 0 {
+        msg.channel_id
+            .say(&ctx.http, "That user isn't banned from games.")
+            .await?;
+    } else {
+        msg.channel_id
+            .say(
+                &ctx.http,
+                format!("Lifted the game ban of {}.", user_id.mention()),
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Lists all users who are banned from signing up for games.
+///
+/// **Usage:** `[p]gamebans`
+///
+/// I need the permission to embed links for this command to work.
+#[command("gamebans")]
+async fn game_bans(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = match msg.guild_id {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let bans = sqlx::query!(
+        "
+        SELECT user_id, reason, banned_by, expires_at FROM game_bans
+        WHERE guild_id = $1 AND (expires_at IS NULL OR expires_at > now())
+        ORDER BY banned_at;
+        ",
+        guild_id.0 as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    if bans.is_empty() {
+        msg.channel_id
+            .say(&ctx.http, "No one is banned from games.")
+            .await?;
+        return Ok(());
+    }
+
+    let mut text = String::new();
+    for ban in bans {
+        let expiry = match ban.expires_at {
+            Some(t) => format!("until {}", t.format("%B %-d, %Y")),
+            None => String::from("permanent"),
+        };
+        let line = format!(
+            "\n<@{}> ({}, by <@{}>): {}",
+            ban.user_id,
+            expiry,
+            ban.banned_by,
+            ban.reason.as_deref().unwrap_or("No reason given.")
+        );
+
+        // Stay within the embed description limit.
+        if text.len() + line.len() > 4000 {
+            text.push_str("\n...");
+            break;
+        }
+        text.push_str(&line);
+    }
+
+    msg.channel_id
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.title("Game Bans");
+                e.description(text.trim());
+                e.colour(EMBED_COLOUR);
+
+                e
+            });
+
+            m
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// A ready check that has just ended.
+pub(crate) struct ReadyCheck {
+    pub(crate) guild_id: i64,
+    pub(crate) channel_id: i64,
+    pub(crate) message_id: i64,
+    pub(crate) results_channel_id: i64,
+    pub(crate) players: Vec<i64>,
+    pub(crate) confirmed: Vec<i64>,
+}
+
+/// Starts a ready check for all players.
+///
+/// **Usage:** `[p]readycheck [minutes]`
+///
+/// Players must react to my message in the sign-ups channel or use the
+/// `ready` command before the time runs out. The time is 30 minutes by
+/// default. When the check ends, I post who confirmed and who didn't in the
+/// channel where this command was used.
+///
+/// Use `[p]readycheck end` to end a ready check early.
+#[command("readycheck")]
+async fn ready_check(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    if args.current() == Some("end") {
+        // The check is ended by the tasks loop, which posts the results.
+        let res = sqlx::query!(
+            "UPDATE ready_checks SET ends_at = now() WHERE guild_id = $1 AND NOT ended;",
+            guild.id.0 as i64
+        )
+        .execute(pool)
+        .await?;
+
+        let reply = if res.rows_affected() == 0 {
+            "There is no ready check running."
+        } else {
+            "Ending the ready check. The results will be posted shortly."
+        };
+        msg.channel_id.say(&ctx.http, reply).await?;
+
+        return Ok(());
+    }
+
+    let minutes = if args.is_empty() {
+        30
+    } else {
+        match args.single::<u32>() {
+            Ok(i) if i > 0 => i,
+            _ => {
+                msg.channel_id
+                    .say(&ctx.http, "`minutes` must be a positive number.")
+                    .await?;
+                return Ok(());
+            }
+        }
+    };
+
+    let running = sqlx::query!(
+        "SELECT ended FROM ready_checks WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if matches!(running, Some(r) if !r.ended) {
+        msg.channel_id
+            .say(&ctx.http, "A ready check is already running.")
+            .await?;
+        return Ok(());
+    }
+
+    let res = sqlx::query!(
+        "SELECT signups_channel_id, player_role_id FROM config WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let role = match get_role(ctx, guild.id, res.player_role_id).await {
+        Ok(r) => r,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, "Player role has not been set up.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let channel = match get_channel_from_id(ctx, guild.id, res.signups_channel_id).await {
+        Ok(c) => c,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, "Sign-ups channel has not been set up.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let players: Vec<i64> = guild
+        .members
+        .values()
+        .filter(|m| m.roles.contains(&role.id))
+        .map(|m| m.user.id.0 as i64)
+        .collect();
+
+    if players.is_empty() {
+        msg.channel_id
+            .say(&ctx.http, "There are no players to check.")
+            .await?;
+        return Ok(());
+    }
+
+    let prefix = match dynamic_prefix(ctx, &msg.guild_id).await {
+        Some(p) => p,
+        None => String::from("-"),
+    };
+
+    let check_msg = channel
+        .say(
+            &ctx.http,
+            format!(
+                "{} **Ready check!** React with ✅ or use `{}ready` within {} minutes \
+                to confirm you're still playing.",
+                role.mention(),
+                prefix,
+                minutes
+            ),
+        )
+        .await?;
+
+    // Players can still use `ready` if the reaction can't be added.
+    let _ = check_msg.react(&ctx.http, '✅').await;
+
+    let ends_at = Utc::now() + chrono::Duration::minutes(minutes as i64);
+
+    sqlx::query!(
+        "
+        INSERT INTO ready_checks(guild_id, channel_id, message_id, results_channel_id, ends_at, players)
+        VALUES($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (guild_id) DO UPDATE
+        SET channel_id = $2, message_id = $3, results_channel_id = $4, ends_at = $5,
+        players = $6, confirmed = '{}', no_shows = NULL, ended = false;
+        ",
+        guild.id.0 as i64,
+        channel.id.0 as i64,
+        check_msg.id.0 as i64,
+        msg.channel_id.0 as i64,
+        ends_at,
+        &players
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!(
+                "Started a ready check for {} players. It ends in {} minutes.",
+                players.len(),
+                minutes
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Posts the results of a ready check which has ended.
+pub(crate) async fn send_ready_results(
+    ctx: &Context,
+    pool: &PgPool,
+    check: ReadyCheck,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let guild_id = GuildId(check.guild_id as u64);
+    let channel_id = ChannelId(check.channel_id as u64);
+
+    let mut confirmed: HashSet<i64> = check.confirmed.into_iter().collect();
+
+    // Collect everyone who reacted, 100 users at a time. If the message is
+    // gone, only players who used `ready` are confirmed.
+    let mut after = None;
+    loop {
+        let users = match channel_id
+            .reaction_users(&ctx.http, check.message_id as u64, '✅', Some(100), after)
+            .await
+        {
+            Ok(u) => u,
+            Err(why) => {
+                tracing::error!("Unable to fetch ready check reactions: {}", why);
+                break;
+            }
+        };
+
+        after = users.last().map(|u| u.id);
+        let done = users.len() < 100;
+        confirmed.extend(users.into_iter().map(|u| u.id.0 as i64));
+
+        if done {
+            break;
+        }
+    }
+
+    // People who are no longer players don't matter anymore.
+    let res = sqlx::query!(
+        "SELECT player_role_id FROM config WHERE guild_id = $1;",
+        check.guild_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let players: Vec<i64> = match (
+        res.player_role_id,
+        ctx.cache.guild_field(guild_id, |g| g.members.clone()).await,
+    ) {
+        (Some(role_id), Some(members)) => check
+            .players
+            .into_iter()
+            .filter(|i| {
+                matches!(
+                    members.get(&UserId(*i as u64)),
+                    Some(m) if m.roles.contains(&RoleId(role_id as u64))
+                )
+            })
+            .collect(),
+        _ => check.players,
+    };
+
+    let (ready, no_shows): (Vec<i64>, Vec<i64>) =
+        players.into_iter().partition(|i| confirmed.contains(i));
+
+    sqlx::query!(
+        "UPDATE ready_checks SET no_shows = $2 WHERE guild_id = $1;",
+        check.guild_id,
+        &no_shows
+    )
+    .execute(pool)
+    .await?;
+
+    let prefix = match dynamic_prefix(ctx, &Some(guild_id)).await {
+        Some(p) => p,
+        None => String::from("-"),
+    };
+
+    ChannelId(check.results_channel_id as u64)
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.title("Ready Check Results");
+                e.colour(EMBED_COLOUR);
+                e.field(
+                    format!("Confirmed ({})", ready.len()),
+                    mention_field(&ready),
+                    false,
+                );
+                e.field(
+                    format!("Not Confirmed ({})", no_shows.len()),
+                    mention_field(&no_shows),
+                    false,
+                );
+                if !no_shows.is_empty() {
+                    e.footer(|f| {
+                        f.text(format!(
+                            "Use {}readydrop to drop the players who didn't confirm.",
+                            prefix
+                        ))
+                    });
+                }
+
+                e
+            });
+
+            m
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Mentions users in an embed field, within the field's length limit.
+fn mention_field(user_ids: &[i64]) -> String {
+    let mut text = String::new();
+    for (idx, id) in user_ids.iter().enumerate() {
+        let mention = format!("<@{}>", id);
+        if text.len() + mention.len() > 1000 {
+            let _ = write!(text, " and {} more", user_ids.len() - idx);
+            break;
+        }
+        if !text.is_empty() {
+            text.push_str(", ");
+        }
+        text.push_str(&mention);
+    }
+
+    if text.is_empty() {
+        String::from("None")
+    } else {
+        text
+    }
+}
+
+/// Drops the players who didn't confirm in the last ready check.
+///
+/// **Usage:** `[p]readydrop`
+///
+/// The players lose the Player role. Replacements are promoted to players in
+/// their place, in the order they signed up in, as long as there are any.
+///
+/// The bot asks for confirmation before making any changes.
+#[command("readydrop")]
+async fn ready_drop(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let check = match sqlx::query!(
+        "SELECT ended, no_shows FROM ready_checks WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await?
+    {
+        Some(c) => c,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, "There is no ready check to act on.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let no_shows = match check.no_shows {
+        Some(n) if check.ended => n,
+        _ => {
+            msg.channel_id
+                .say(&ctx.http, "The ready check hasn't ended yet.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let res = sqlx::query!(
+        "SELECT player_role_id, repl_role_id, signups_channel_id FROM config WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let player_role = match get_role(ctx, guild.id, res.player_role_id).await {
+        Ok(r) => r,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, "Player role has not been set up.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let to_drop: Vec<&Member> = guild
+        .members
+        .values()
+        .filter(|m| m.roles.contains(&player_role.id) && no_shows.contains(&(m.user.id.0 as i64)))
+        .collect();
+
+    if to_drop.is_empty() {
+        msg.channel_id
+            .say(
+                &ctx.http,
+                "Everyone who is still signed up confirmed. There is no one to drop.",
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let confirm_msg = msg
+        .channel_id
+        .say(
+            &ctx.http,
+            format!(
+                "Are you sure you want to drop {} players who didn't confirm?",
+                to_drop.len()
+            ),
+        )
+        .await?;
+
+    if !yes_or_no_prompt(ctx, &confirm_msg, &msg.author, 30.0).await? {
+        msg.channel_id
+            .say(&ctx.http, "Cancelled dropping players.")
+            .await?;
+        return Ok(());
+    }
+
+    let mut dropped = Vec::new();
+    for member in &to_drop {
+        let mut member = (*member).clone();
+        if member.remove_role(&ctx.http, player_role.id).await.is_err() {
+            continue;
+        }
+
+        sqlx::query!(
+            "
+            UPDATE config SET total_signups = coalesce(total_signups, 1) - 1,
+            players = array_remove(players, $2)
+            WHERE guild_id = $1;
+            ",
+            guild.id.0 as i64,
+            member.user.id.0 as i64
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query!(
+            "DELETE FROM signup_prefs WHERE guild_id = $1 AND user_id = $2;",
+            guild.id.0 as i64,
+            member.user.id.0 as i64
+        )
+        .execute(pool)
+        .await?;
+
+        dropped.push(member.user.id.0 as i64);
+    }
+
+    let mut promoted = Vec::new();
+    if let Some(repl_role_id) = res.repl_role_id {
+        let repl_role_id = RoleId(repl_role_id as u64);
+        let order: Vec<i64> = sqlx::query!(
+            "SELECT user_id FROM replacement_signups WHERE guild_id = $1 ORDER BY created_at;",
+            guild.id.0 as i64
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.user_id)
+        .collect();
+
+        // Replacements are promoted in the order they signed up in.
+        let mut replacements: Vec<&Member> = guild
+            .members
+            .values()
+            .filter(|m| m.roles.contains(&repl_role_id))
+            .collect();
+        replacements.sort_by_key(|m| {
+            order
+                .iter()
+                .position(|i| *i == m.user.id.0 as i64)
+                .unwrap_or(usize::MAX)
+        });
+
+        for member in replacements.into_iter().take(dropped.len()) {
+            let mut member = member.clone();
+            if member.add_role(&ctx.http, player_role.id).await.is_err() {
+                continue;
+            }
+            let _ = member.remove_role(&ctx.http, repl_role_id).await;
+
+            sqlx::query!(
+                "
+                UPDATE config SET total_signups = coalesce(total_signups, 0) + 1,
+                players = array_append(players, $2)
+                WHERE guild_id = $1;
+                ",
+                guild.id.0 as i64,
+                member.user.id.0 as i64
+            )
+            .execute(pool)
+            .await?;
+
+            sqlx::query!(
+                "DELETE FROM replacement_signups WHERE guild_id = $1 AND user_id = $2;",
+                guild.id.0 as i64,
+                member.user.id.0 as i64
+            )
+            .execute(pool)
+            .await?;
+
+            promoted.push(member.user.id.0 as i64);
+        }
+    }
+
+    sqlx::query!(
+        "DELETE FROM ready_checks WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .execute(pool)
+    .await?;
+
+    update_signup_board(ctx, guild.id).await;
+
+    if !promoted.is_empty() {
+        if let Ok(channel) = get_channel_from_id(ctx, guild.id, res.signups_channel_id).await {
+            channel
+                .say(
+                    &ctx.http,
+                    format!(
+                        "{} You have been promoted from replacement to player!",
+                        mention_field(&promoted)
+                    ),
+                )
+                .await?;
+        }
+    }
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!(
+                "Dropped: {}\nPromoted: {}",
+                mention_field(&dropped),
+                mention_field(&promoted)
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Gives a player an alias for anonymous games.
+///
+/// **Usage:** `[p]alias <player> [name [avatar_url]]`
+///
+/// Use quotes around the alias if it has spaces. The avatar must be a link to
+/// an image. Use the command without a name to remove the alias of a player.
+///
+/// **Example**
+///
+/// Command: `[p]alias Arius "Blue Fox" https://example.com/fox.png`
+#[command("alias")]
+#[min_args(1)]
+async fn set_alias(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let input = args.single_quoted::<String>()?;
+    let member = match get_member(ctx, guild.id, Some(&input)).await {
+        Ok(m) => m,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, format!("No member found from `{}`.", input))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let alias = match args.single_quoted::<String>() {
+        Ok(a) => a,
+        Err(_) => {
+            sqlx::query!(
+                "DELETE FROM aliases WHERE guild_id = $1 AND user_id = $2;",
+                guild.id.0 as i64,
+                member.user.id.0 as i64
+            )
+            .execute(pool)
+            .await?;
+
+            msg.channel_id.say(&ctx.http, "Removed the alias.").await?;
+            return Ok(());
+        }
+    };
+    let avatar_url = args.single::<String>().ok();
+
+    // Webhook names have to be between 1 and 80 characters long.
+    if alias.is_empty() || alias.chars().count() > 80 {
+        msg.channel_id
+            .say(
+                &ctx.http,
+                "The alias must be between 1 and 80 characters long.",
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let res = sqlx::query!(
+        "
+        INSERT INTO aliases(guild_id, user_id, alias, avatar_url) VALUES($1, $2, $3, $4)
+        ON CONFLICT (guild_id, user_id) DO UPDATE SET alias = $3, avatar_url = $4;
+        ",
+        guild.id.0 as i64,
+        member.user.id.0 as i64,
+        alias,
+        avatar_url
+    )
+    .execute(pool)
+    .await;
+
+    if res.is_err() {
+        msg.channel_id
+            .say(&ctx.http, "Another player already has that alias.")
+            .await?;
+        return Ok(());
+    }
+
+    // Don't leave the real name next to the alias in a public channel.
+    let _ = msg.delete(&ctx.http).await;
+    msg.channel_id
+        .say(&ctx.http, format!("Set the alias **{}**.", alias))
+        .await?;
+
+    Ok(())
+}
+
+/// Sends you the list of aliases and who they belong to.
+///
+/// **Usage:** `[p]aliases`
+///
+/// The list is sent in your DMs so it stays private.
+#[command("aliases")]
+async fn list_aliases(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = match msg.guild_id {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let aliases = sqlx::query!(
+        "SELECT user_id, alias FROM aliases WHERE guild_id = $1 ORDER BY alias;",
+        guild_id.0 as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    if aliases.is_empty() {
+        msg.channel_id
+            .say(&ctx.http, "No one has an alias yet.")
+            .await?;
+        return Ok(());
+    }
+
+    let mut text = String::new();
+    for a in aliases {
+        write!(text, "\n**{}**: <@{}>", a.alias, a.user_id)?;
+    }
+
+    let sent = msg
+        .author
+        .direct_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.title("Aliases");
+                e.description(text.trim());
+                e.colour(EMBED_COLOUR);
+
+                e
+            });
+
+            m
+        })
+        .await;
+
+    let reply = if sent.is_ok() {
+        "Sent you the list of aliases in DMs."
+    } else {
+        "I couldn't DM you. Please check your privacy settings."
+    };
+    msg.channel_id.say(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+/// Links two channels until the phase changes.
+///
+/// **Usage:** `[p]link <channel|player> <channel|player>`
+///
+/// Messages sent in either channel are copied to the other one. You can give
+/// a player instead of a channel to link their private chat. Depending on the
+/// `tvm anonlinks` setting, messages show the author's name or "Anonymous".
+///
+/// The link ends when the `cycle` or `night` command is used. A channel can
+/// only be in one link at a time.
+///
+/// **Example**
+///
+/// Command: `[p]link #dead-chat Arius`
+#[command("link")]
+#[min_args(2)]
+async fn link_channels(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let bot_id = ctx.cache.current_user_id().await;
+    let mut channels = Vec::new();
+    for _ in 0..2 {
+        let input = args.single_quoted::<String>()?;
+        let channel = match get_channel(ctx, guild.id, Some(&input)).await {
+            Ok(c) => Some(c),
+            Err(_) => match get_member(ctx, guild.id, Some(&input)).await {
+                Ok(m) => find_private_chat(pool, &guild, m.user.id, bot_id).await,
+                Err(_) => None,
+            },
+        };
+
+        match channel {
+            Some(c) => channels.push(c),
+            None => {
+                msg.channel_id
+                    .say(
+                        &ctx.http,
+                        format!("No channel or private chat found from `{}`.", input),
+                    )
+                    .await?;
+                return Ok(());
+            }
         }
-    };
-
-    if !member.roles.contains(&player_role.id) {
-        msg.channel_id
-            .say(&ctx.http, "User doesn't have the player role!")
-            .await?;
-        return Ok(());
     }
 
-    // Remove player role.
-    if member.remove_role(&ctx.http, player_role.id).await.is_err() {
+    if channels[0].id == channels[1].id {
         msg.channel_id
-            .say(&ctx.http, "I couldn't remove player role from the user.")
+            .say(&ctx.http, "You can't link a channel to itself.")
             .await?;
         return Ok(());
     }
 
-    // Add dead player role.
-    if member.add_role(&ctx.http, dead_role.id).await.is_err() {
+    let linked = sqlx::query!(
+        "
+        SELECT EXISTS(
+            SELECT 1 FROM channel_links WHERE guild_id = $1
+            AND (first_channel_id = ANY($2) OR second_channel_id = ANY($2))
+        ) AS \"linked!\";
+        ",
+        guild.id.0 as i64,
+        &channels.iter().map(|c| c.id.0 as i64).collect::<Vec<_>>()
+    )
+    .fetch_one(pool)
+    .await?
+    .linked;
+
+    if linked {
         msg.channel_id
             .say(
                 &ctx.http,
-                "I couldn't add the dead player role to the user.",
+                "One of the channels is already linked. Use `unlink` first.",
             )
             .await?;
         return Ok(());
     }
 
+    sqlx::query!(
+        "INSERT INTO channel_links(guild_id, first_channel_id, second_channel_id) VALUES($1, $2, $3);",
+        guild.id.0 as i64,
+        channels[0].id.0 as i64,
+        channels[1].id.0 as i64
+    )
+    .execute(pool)
+    .await?;
+
     msg.channel_id
         .say(
             &ctx.http,
-            "Removed player role and added dead player role to the user!",
+            format!(
+                "Linked {} and {} until the phase changes.",
+                channels[0].mention(),
+                channels[1].mention()
+            ),
         )
         .await?;
 
     Ok(())
 }
 
-/// Generates player list and sends it in the specified channel.
-///
-/// **Usage:** `[p]playerlist <channel>`
-///
-/// **Aliases:** `plist`, `pl`
-///
-/// The player role must be set for this command to work.
+/// Removes the link of a channel, or all links if no channel is given.
 ///
-/// I need the permission to embed links in the specified channel.
-#[command("playerlist")]
-#[aliases("plist", "pl")]
-#[min_args(1)]
-async fn player_list(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+/// **Usage:** `[p]unlink [channel|player]`
+#[command("unlink")]
+async fn unlink_channels(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     let guild = match msg.guild(ctx).await {
         Some(i) => i,
         None => return Err(CommandError::from("Couldn't fetch details of this server.")),
     };
 
-    // Get argument channel.
-    let input = args.message().to_string();
-    let channel = match get_channel(&ctx, guild.id, Some(&input)).await {
-        Ok(c) => c,
-        Err(_) => {
-            msg.channel_id
-                .say(&ctx.http, format!("No channel found from `{}`.", input))
-                .await?;
-            return Ok(());
-        }
-    };
-
     let data_read = ctx.data.read().await;
     let pool = data_read.get::<ConnectionPool>().unwrap();
 
-    let res = match sqlx::query!(
-        "SELECT player_role_id FROM config WHERE guild_id = $1;",
-        guild.id.0 as i64
-    )
-    .fetch_one(pool)
-    .await
-    {
-        Ok(i) => i,
-        Err(_) => {
-            return Err(CommandError::from(
-                "Couldn't fetch details from the database.",
-            ))
-        }
-    };
+    if args.is_empty() {
+        end_links(ctx, guild.id, pool, true).await;
+        msg.channel_id
+            .say(&ctx.http, "Removed all channel links.")
+            .await?;
+        return Ok(());
+    }
 
-    let role = match get_role(ctx, guild.id, res.player_role_id).await {
-        Ok(r) => r,
-        Err(_) => {
+    let input = args.message().to_string();
+    let channel = match get_channel(ctx, guild.id, Some(&input)).await {
+        Ok(c) => Some(c),
+        Err(_) => match get_member(ctx, guild.id, Some(&input)).await {
+            Ok(m) => {
+                let bot_id = ctx.cache.current_user_id().await;
+                find_private_chat(pool, &guild, m.user.id, bot_id).await
+            }
+            Err(_) => None,
+        },
+    };
+    let channel = match channel {
+        Some(c) => c,
+        None => {
             msg.channel_id
-                .say(&ctx.http, "Player role has not been set up.")
+                .say(
+                    &ctx.http,
+                    format!("No channel or private chat found from `{}`.", input),
+                )
                 .await?;
             return Ok(());
         }
     };
 
-    let players: Vec<_> = guild
-        .members
-        .values()
-        .filter_map(|m| {
-            if m.roles.contains(&role.id) {
-                Some(m.mention())
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    if players.is_empty() {
-        msg.channel_id.say(&ctx.http, "No players!").await?;
-        return Ok(());
-    }
-
-    let mut players_str = String::new();
-    for (i, player) in players.iter().enumerate() {
-        let _ = write!(players_str, "\n{}. {}", i + 1, player);
-    }
-
-    let sent = channel
-        .send_message(&ctx.http, |m| {
-            m.embed(|e| {
-                e.title("Player List");
-                e.description(players_str);
-                e.colour(role.colour);
-                e.footer(|f| {
-                    f.text(format!("Total Players: {0}", players.len()));
-
-                    f
-                });
-
-                e
-            });
-
-            m
-        })
-        .await;
+    let res = sqlx::query!(
+        "
+        DELETE FROM channel_links
+        WHERE guild_id = $1 AND (first_channel_id = $2 OR second_channel_id = $2);
+        ",
+        guild.id.0 as i64,
+        channel.id.0 as i64
+    )
+    .execute(pool)
+    .await?;
 
-    if sent.is_err() {
-        msg.channel_id.say(
-            &ctx.http,
-            format!(
-                "I couldn't send the player list. Please check if I have permissions to embed link in {}.",
-                channel.mention()
-            )
-        ).await?;
-    }
+    let reply = if res.rows_affected() == 0 {
+        format!("{} isn't linked to any channel.", channel.mention())
+    } else {
+        format!("Removed the link of {}.", channel.mention())
+    };
+    msg.channel_id.say(&ctx.http, reply).await?;
 
     Ok(())
 }
 
-/// Shows information about the current cycle.
+/// Shows the audit log of host commands.
 ///
-/// **Usage:** `[p]current`
+/// **Usage:** `[p]audit [user|command] [limit]`
 ///
-/// I need the permission to embed links in the specified channel.
-#[command]
-async fn current(ctx: &Context, msg: &Message) -> CommandResult {
-    let guild = match msg.guild(ctx).await {
+/// Every command of the host and `tvm` groups is recorded with who used it, its
+/// arguments and whether it ended with an error. Commands which only reply that
+/// something is wrong, like a member that can't be found, aren't marked. You can
+/// show the commands of one user, or the uses of one command, like `kill` or
+/// `tvm signopen`.
+///
+/// The last 10 commands are shown by default, and up to 50 at once.
+#[command("audit")]
+async fn audit_log(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild_id = match msg.guild_id {
         Some(i) => i,
         None => return Err(CommandError::from("Couldn't fetch details of this server.")),
     };
 
+    let mut words: Vec<&str> = args.message().split_whitespace().collect();
+    // A lone number out of range is the ID of a user, not a limit.
+    let limit = match words.last().map(|w| w.parse::<i64>()) {
+        Some(Ok(l)) if (1..=50).contains(&l) || words.len() > 1 => {
+            words.pop();
+            l.clamp(1, 50)
+        }
+        _ => 10,
+    };
+
+    let filter = words.join(" ");
+    let mut user_id = None;
+    let mut command = None;
+    if !filter.is_empty() {
+        if let Some(name) = audited_command_name(&filter) {
+            command = Some(name);
+        } else if let Some(member) = to_member(ctx, guild_id, &filter).await {
+            user_id = Some(member.user.id.0 as i64);
+        } else {
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    format!("No audited command or member found from `{}`.", filter),
+                )
+                .await?;
+            return Ok(());
+        }
+    }
+
     let data_read = ctx.data.read().await;
     let pool = data_read.get::<ConnectionPool>().unwrap();
 
-    let cycle: Cycle = match sqlx::query_as_unchecked!(
-        CycleContainer,
-        "SELECT cycle FROM config WHERE guild_id = $1;",
-        guild.id.0 as i64
+    let entries = sqlx::query!(
+        "
+        SELECT user_id, command, args, success, created_at FROM command_audit
+        WHERE guild_id = $1 AND ($2::bigint IS NULL OR user_id = $2)
+        AND ($3::text IS NULL OR command = $3)
+        ORDER BY created_at DESC LIMIT $4;
+        ",
+        guild_id.0 as i64,
+        user_id,
+        command,
+        limit
     )
-    .fetch_one(pool)
-    .await
-    {
-        Ok(cf) => {
-            if let Some(c) = cf.cycle {
-                c.0
-            } else {
-                Cycle {
-                    number: 0,
-                    day: None,
-                    night: None,
-                    votes: None,
-                }
-            }
+    .fetch_all(pool)
+    .await?;
+
+    if entries.is_empty() {
+        msg.channel_id
+            .say(&ctx.http, "No commands were recorded yet.")
+            .await?;
+        return Ok(());
+    }
+
+    let mut description = String::new();
+    for entry in entries {
+        let mut used = format!("{} {}", entry.command, entry.args.replace('`', "'"));
+        if let Some((end, _)) = used.char_indices().nth(100) {
+            used.truncate(end);
+            used.push_str("...");
         }
-        Err(_) => {
-            return Err(CommandError::from(
-                "Couldn't fetch details from the database.",
-            ))
+        let line = format!(
+            "`{}` <@{}> `{}`{}\n",
+            entry.created_at.format("%F %R"),
+            entry.user_id,
+            used.trim(),
+            if entry.success { "" } else { " (error)" }
+        );
+
+        if description.len() + line.len() > 2048 {
+            break;
         }
-    };
-
-    let day = match cycle.day {
-        Some(i) => format!("<#{}>", i),
-        None => String::from("Not set"),
-    };
-    let voting = match cycle.votes {
-        Some(i) => format!("<#{}>", i),
-        None => String::from("Not set"),
-    };
-    let night = match cycle.night {
-        Some(i) => format!("<#{}>", i),
-        None => String::from("Not set"),
-    };
+        description.push_str(&line);
+    }
 
-    let sent = msg
-        .channel_id
+    msg.channel_id
         .send_message(&ctx.http, |m| {
             m.embed(|e| {
+                e.title("Audit Log");
+                e.description(description);
                 e.colour(EMBED_COLOUR);
-                e.title(format!("Cycle Number {}", cycle.number));
-                e.description(format!(
-                    "**Day:** {}\n**Voting:** {}\n**Night:** {}",
-                    day, voting, night
-                ));
+                e.footer(|f| {
+                    f.text("Times are in UTC.");
+
+                    f
+                });
 
                 e
             });
 
             m
         })
-        .await;
-
-    if sent.is_err() {
-        msg.channel_id
-            .say(&ctx.http, "I couldn't send an embed.")
-            .await?;
-    }
-
-    Ok(())
-}
-
-/// Announces a message in an embed.
-///
-/// **Usage:** `[p]announce [channel] <msg>`
-///
-/// You can optionally specify a channel by its ID or mention to send the
-/// embed announcement in that channel. The actual `msg` can contain hyperlinks
-/// in addition to normal Discord Markdown formatting.
-///
-/// I need the permission to embed links in the specified channel.
-#[command]
-#[min_args(1)]
-async fn announce(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
-    let arg = args.current().unwrap();
-    let channel_id = if let Some(c) =
-        GuildChannel::from_guild_id_and_str(ctx, msg.guild_id.unwrap(), arg).await
-    {
-        args.advance();
-
-        c.id
-    } else {
-        msg.channel_id
-    };
-
-    if args.rest().is_empty() {
-        msg.channel_id
-            .say(&ctx.http, "Expected message to announce!")
-            .await?;
-    }
-
-    channel_id
-        .send_message(&ctx.http, |m| {
-            m.embed(|e| {
-                e.description(args.rest())
-                    .timestamp(Utc::now().to_rfc3339())
-                    .colour(EMBED_COLOUR)
-            })
-        })
         .await?;
 
     Ok(())
@@ -1456,16 +4348,35 @@ async fn announce(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult
 #[checks("is_host_or_admin")]
 #[commands(
     randomize_roles,
+    player_prefs,
     sync_total,
     total_signups,
     players_chats,
+    pc_delete,
+    pc_archive,
     spec_chat,
     mafia_chat,
+    faction_chat,
+    faction_add,
+    faction_remove,
+    faction_chats,
+    temp_chat,
     create_cycle,
     night,
     kill_player,
+    revive_player,
     player_list,
     current,
-    announce
+    announce,
+    game_ban,
+    game_unban,
+    game_bans,
+    ready_check,
+    ready_drop,
+    set_alias,
+    list_aliases,
+    link_channels,
+    unlink_channels,
+    audit_log
 )]
 struct Utilities;
@@ -2,20 +2,26 @@
 //! Message related events fired by serenity are handled in `/src/events.rs`.
 
 use crate::{
-    events::{is_allowed_channel, LogSettings},
+    events::{is_allowed_channel, LogEvent, LogSettings, UPLOAD_LIMIT},
     utils::{
         checks::*,
-        converters::{get_channel, get_channel_from_id},
+        converters::{get_channel, get_channel_from_id, parse_datetime, to_channel, to_member},
     },
     ConnectionPool,
 };
 use serenity::{
+    builder::CreateMessage,
     framework::standard::{
         macros::{command, group},
         Args, CommandError, CommandResult,
     },
     model::{misc::Mentionable, prelude::*},
     prelude::Context,
+    utils::parse_username,
+};
+use serenity_utils::{
+    formatting::text_to_file,
+    menu::{Menu, MenuOptions},
 };
 use std::fmt::Write;
 
@@ -328,6 +334,751 @@ async fn remove_blacklist_channel(ctx: &Context, msg: &Message, args: Args) -> C
     Ok(())
 }
 
+/// Toggles the message archive.
+///
+/// Usage: `[p]log archive [setting]`
+///
+/// When enabled, I save the content, attachment details and author of every
+/// message sent in logged channels. Deleted and edited messages are then logged
+/// with their original content, even if I restarted after they were sent.
+///
+/// You can optionally specify the setting to use. `setting` can be one of
+/// - `true`
+/// - `false`
+#[command("archive")]
+async fn log_archive(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let mut toggle = false;
+    let mut setting = false;
+
+    if args.is_empty() {
+        toggle = true;
+    } else {
+        setting = match args.single() {
+            Ok(s) => s,
+            Err(_) => return Err(CommandError::from("Invalid option.")),
+        };
+    }
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    if toggle {
+        setting = match sqlx::query!(
+            "SELECT archive_on FROM logging WHERE guild_id = $1",
+            msg.guild_id.unwrap().0 as i64
+        )
+        .fetch_optional(pool)
+        .await?
+        {
+            Some(r) => !r.archive_on,
+            None => true,
+        };
+    }
+
+    sqlx::query!(
+        "
+        INSERT INTO logging(guild_id, archive_on) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET archive_on = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        setting
+    )
+    .execute(pool)
+    .await?;
+
+    let reply = if setting {
+        "Messages in logged channels will now be archived."
+    } else {
+        "Messages won't be archived anymore."
+    };
+    msg.channel_id.say(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+/// Sets how many days archived messages are kept.
+///
+/// Usage: `[p]log retention <days>`
+///
+/// Messages are kept for 30 days by default.
+#[command("retention")]
+#[min_args(1)]
+async fn log_retention(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let days = match args.single::<i32>() {
+        Ok(d) if d > 0 => d,
+        _ => {
+            msg.channel_id
+                .say(&ctx.http, "Please give a number of days above 0.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    sqlx::query!(
+        "
+        INSERT INTO logging(guild_id, archive_retention) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET archive_retention = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        days
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!("Archived messages will be kept for {} days.", days),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Toggles saving attachments of messages in logged channels.
+///
+/// Usage: `[p]log attachments [setting]`
+///
+/// When enabled, I download the attachments of messages sent in logged channels.
+/// If a message is deleted, its attachments are uploaded again in the log channel.
+/// Files larger than the size limit set with `[p]log filesize` are not saved.
+///
+/// You can optionally specify the setting to use. `setting` can be one of
+/// - `true`
+/// - `false`
+#[command("attachments")]
+async fn log_attachments(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let mut toggle = false;
+    let mut setting = false;
+
+    if args.is_empty() {
+        toggle = true;
+    } else {
+        setting = match args.single() {
+            Ok(s) => s,
+            Err(_) => return Err(CommandError::from("Invalid option.")),
+        };
+    }
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    if toggle {
+        setting = match sqlx::query!(
+            "SELECT attachments_on FROM logging WHERE guild_id = $1",
+            msg.guild_id.unwrap().0 as i64
+        )
+        .fetch_optional(pool)
+        .await?
+        {
+            Some(r) => !r.attachments_on,
+            None => true,
+        };
+    }
+
+    sqlx::query!(
+        "
+        INSERT INTO logging(guild_id, attachments_on) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET attachments_on = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        setting
+    )
+    .execute(pool)
+    .await?;
+
+    let reply = if setting {
+        "Attachments in logged channels will now be saved."
+    } else {
+        "Attachments won't be saved anymore."
+    };
+    msg.channel_id.say(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+/// Sets the largest attachment I save, in megabytes.
+///
+/// Usage: `[p]log filesize <size>`
+///
+/// The size can be between 1 and 8 MB, since I can't upload larger files.
+/// It is 8 MB by default.
+#[command("filesize")]
+#[min_args(1)]
+async fn log_file_size(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let size = match args.single::<i32>() {
+        Ok(s) if (1..=(UPLOAD_LIMIT / 1024 / 1024) as i32).contains(&s) => s,
+        _ => {
+            msg.channel_id
+                .say(&ctx.http, "Please give a size between 1 and 8 MB.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    sqlx::query!(
+        "
+        INSERT INTO logging(guild_id, attachment_max_size) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET attachment_max_size = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        size
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!("I will save attachments up to {} MB.", size),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Sets how many days saved attachments are kept.
+///
+/// Usage: `[p]log fileretention <days>`
+///
+/// Attachments are kept for 7 days by default.
+#[command("fileretention")]
+#[min_args(1)]
+async fn log_file_retention(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let days = match args.single::<i32>() {
+        Ok(d) if d > 0 => d,
+        _ => {
+            msg.channel_id
+                .say(&ctx.http, "Please give a number of days above 0.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    sqlx::query!(
+        "
+        INSERT INTO logging(guild_id, attachment_retention) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET attachment_retention = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        days
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!("Saved attachments will be kept for {} days.", days),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Turns logging of a kind of event on or off.
+///
+/// Usage: `[p]log event <event> [setting]`
+///
+/// `event` can be one of
+/// - `edits`: Edited messages
+/// - `deletes`: Deleted messages
+/// - `roles`: Game roles given to or taken from members
+/// - `nicknames`: Nickname changes
+/// - `joins`: Members joining and leaving
+/// - `overwrites`: Permission changes in logged channels and the game channels I manage
+/// - `ghostpings`: Mentions removed by deleting or editing a message
+/// - `votes`: Votes changed by editing or deleting a message in the voting channel. Votes are
+///   archived while this is on, even if the message archive is off.
+/// - `audit`: Host commands, see `[p]audit`
+///
+/// All events except `audit` are logged by default. Servers that had a log
+/// channel before the other events were added only log `edits` and `deletes`
+/// until they turn the rest on. The whitelist and blacklist apply to
+/// message and permission events, which happen in a channel. Ghost pings are
+/// checked in all channels except blacklisted ones.
+///
+/// You can optionally specify the setting to use. `setting` can be one of
+/// - `true`
+/// - `false`
+#[command("event")]
+#[min_args(1)]
+async fn log_event(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let event = match LogEvent::from_name(&args.single::<String>()?) {
+        Some(e) => e,
+        None => {
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    format!("Please choose one of these events: {}.", event_names()),
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let setting = if args.is_empty() {
+        match sqlx::query!(
+            "SELECT disabled_events FROM logging WHERE guild_id = $1",
+            msg.guild_id.unwrap().0 as i64
+        )
+        .fetch_optional(pool)
+        .await?
+        {
+            Some(r) => r.disabled_events.iter().any(|e| e == event.name()),
+            // Only the audit is off without settings.
+            None => event == LogEvent::Audit,
+        }
+    } else {
+        match args.single() {
+            Ok(s) => s,
+            Err(_) => return Err(CommandError::from("Invalid option.")),
+        }
+    };
+
+    // The event is removed first, so it is never in the list twice.
+    sqlx::query!(
+        "
+        INSERT INTO logging(guild_id, disabled_events)
+        VALUES($1, CASE WHEN $3 THEN array_remove('{audit}', $2) ELSE array_append(array_remove('{audit}', $2), $2) END)
+        ON CONFLICT (guild_id) DO UPDATE SET disabled_events = CASE
+            WHEN $3 THEN array_remove(logging.disabled_events, $2)
+            ELSE array_append(array_remove(logging.disabled_events, $2), $2)
+        END;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        event.name(),
+        setting
+    )
+    .execute(pool)
+    .await?;
+
+    let reply = if setting {
+        format!("{} will now be logged.", event.description())
+    } else {
+        format!("{} won't be logged anymore.", event.description())
+    };
+    msg.channel_id.say(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+/// Chooses where ghost ping alerts are sent.
+///
+/// Usage: `[p]log ghostpings [setting]`
+///
+/// When a message that mentions someone is deleted, or edited to remove the
+/// mention, I send an alert naming the author and the pinged members. Alerts
+/// are sent in the log channel by default. Turn this setting on to send them in
+/// the channel of the message instead. Alerts in the channel don't show the
+/// content of the message.
+///
+/// Use `[p]log event ghostpings false` to turn alerts off.
+///
+/// You can optionally specify the setting to use. `setting` can be one of
+/// - `true`
+/// - `false`
+#[command("ghostpings")]
+async fn log_ghost_pings(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let mut toggle = false;
+    let mut setting = false;
+
+    if args.is_empty() {
+        toggle = true;
+    } else {
+        setting = match args.single() {
+            Ok(s) => s,
+            Err(_) => return Err(CommandError::from("Invalid option.")),
+        };
+    }
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    if toggle {
+        setting = match sqlx::query!(
+            "SELECT ghost_pings_in_channel FROM logging WHERE guild_id = $1",
+            msg.guild_id.unwrap().0 as i64
+        )
+        .fetch_optional(pool)
+        .await?
+        {
+            Some(r) => !r.ghost_pings_in_channel,
+            None => true,
+        };
+    }
+
+    sqlx::query!(
+        "
+        INSERT INTO logging(guild_id, ghost_pings_in_channel) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET ghost_pings_in_channel = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        setting
+    )
+    .execute(pool)
+    .await?;
+
+    let reply = if setting {
+        "Ghost ping alerts will be sent in the channel of the message."
+    } else {
+        "Ghost ping alerts will be sent in the log channel."
+    };
+    msg.channel_id.say(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+/// Chooses the channel a kind of event is logged in.
+///
+/// Usage: `[p]log route <event> [channel]`
+///
+/// `event` can be any of the events `[p]log event` accepts. Events are logged in
+/// the channel set with `[p]log channel` unless they have a channel of their own.
+/// Leave out `channel` to log the event in that channel again.
+#[command("route")]
+#[min_args(1)]
+async fn log_route(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let event = match LogEvent::from_name(&args.single::<String>()?) {
+        Some(e) => e,
+        None => {
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    format!("Please choose one of these events: {}.", event_names()),
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let guild_id = msg.guild_id.unwrap();
+
+    if args.is_empty() {
+        sqlx::query!(
+            "DELETE FROM log_routes WHERE guild_id = $1 AND event = $2;",
+            guild_id.0 as i64,
+            event.name()
+        )
+        .execute(pool)
+        .await?;
+
+        msg.channel_id
+            .say(
+                &ctx.http,
+                format!(
+                    "{} will be logged in the default log channel.",
+                    event.description()
+                ),
+            )
+            .await?;
+
+        return Ok(());
+    }
+
+    let input = args.rest().to_string();
+    let channel = match get_channel(ctx, guild_id, Some(&input)).await {
+        Ok(c) => c,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, format!("No channel found from `{}`.", input))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    sqlx::query!(
+        "
+        INSERT INTO log_routes(guild_id, event, channel_id) VALUES($1, $2, $3)
+        ON CONFLICT (guild_id, event) DO UPDATE SET channel_id = $3;
+        ",
+        guild_id.0 as i64,
+        event.name(),
+        channel.id.0 as i64
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!(
+                "{} will be logged in {}.",
+                event.description(),
+                channel.mention()
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Lists the names of all events, for error messages.
+fn event_names() -> String {
+    LogEvent::ALL
+        .iter()
+        .map(|e| format!("`{}`", e.name()))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// How many messages are shown on each page of search results.
+const SEARCH_PAGE_SIZE: usize = 5;
+
+/// Most messages a search returns.
+const SEARCH_LIMIT: i64 = 1000;
+
+/// Searches messages in the message archive.
+///
+/// **Usage:** `[p]log search [filters] [text]`
+///
+/// Only messages saved while the message archive was on can be found, see
+/// `[p]log archive`. `text` is searched in the content of messages, ignoring case.
+///
+/// Filters can be any of
+/// - `--author <user>`: Messages sent by a member, or by a user who left given their ID
+/// - `--channel <channel>`: Messages sent in a channel
+/// - `--after <date>`: Messages sent after a date, like `2021-05-02` or `"2021-05-02 18:30"`
+/// - `--before <date>`: Messages sent before a date
+/// - `--deleted`: Only deleted messages
+/// - `--edited`: Only edited messages
+/// - `--export`: Send all matches as a file instead of pages
+///
+/// Dates are in UTC. The newest messages are shown first. At most 1000
+/// messages are returned, so narrow the search if it is capped.
+#[command("search")]
+async fn log_search(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild_id = match msg.guild_id {
+        Some(i) => i,
+        None => return Err(CommandError::from("I couldn't fetch server details.")),
+    };
+
+    let mut author_id = None;
+    let mut channel_id = None;
+    let mut after = None;
+    let mut before = None;
+    let mut deleted_only = false;
+    let mut edited_only = false;
+    let mut export = false;
+    let mut words = Vec::new();
+
+    while !args.is_empty() {
+        let arg = args.single_quoted::<String>()?;
+        match arg.as_str() {
+            "--deleted" => deleted_only = true,
+            "--edited" => edited_only = true,
+            "--export" => export = true,
+            "--author" | "--channel" | "--after" | "--before" => {
+                let value = match args.single_quoted::<String>() {
+                    Ok(v) => v,
+                    Err(_) => {
+                        msg.channel_id
+                            .say(&ctx.http, format!("Please give a value after `{}`.", arg))
+                            .await?;
+                        return Ok(());
+                    }
+                };
+
+                let parsed = match arg.as_str() {
+                    "--author" => match to_member(ctx, guild_id, &value).await {
+                        Some(m) => {
+                            author_id = Some(m.user.id.0 as i64);
+                            Ok(())
+                        }
+                        // Players who left can still be searched by ID.
+                        None => value
+                            .parse::<u64>()
+                            .ok()
+                            .or_else(|| parse_username(&value))
+                            .map(|i| author_id = Some(i as i64))
+                            .ok_or("No member or user ID found from `{}`."),
+                    },
+                    "--channel" => to_channel(ctx, guild_id, &value)
+                        .await
+                        .map(|c| channel_id = Some(c.id.0 as i64))
+                        .ok_or("No channel found from `{}`."),
+                    _ => {
+                        let mut parts = value.split_whitespace();
+                        let date = parts.next().unwrap_or_default();
+                        let time = parts.next().unwrap_or("00:00");
+                        match parse_datetime(date, time, None) {
+                            Ok(dt) if arg == "--after" => {
+                                after = Some(dt);
+                                Ok(())
+                            }
+                            Ok(dt) => {
+                                before = Some(dt);
+                                Ok(())
+                            }
+                            Err(why) => Err(why),
+                        }
+                    }
+                };
+
+                if let Err(why) = parsed {
+                    msg.channel_id
+                        .say(&ctx.http, why.replace("{}", &value))
+                        .await?;
+                    return Ok(());
+                }
+            }
+            _ => words.push(arg),
+        }
+    }
+
+    let text = if words.is_empty() {
+        None
+    } else {
+        Some(words.join(" "))
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let matches = sqlx::query!(
+        "
+        SELECT channel_id, author_id, author_tag, content, created_at,
+        edited_at, deleted_at FROM message_archive
+        WHERE guild_id = $1
+        AND ($2::bigint IS NULL OR author_id = $2)
+        AND ($3::bigint IS NULL OR channel_id = $3)
+        AND ($4::timestamptz IS NULL OR created_at >= $4)
+        AND ($5::timestamptz IS NULL OR created_at < $5)
+        AND ($6::text IS NULL OR strpos(lower(content), lower($6)) > 0)
+        AND (NOT $7 OR deleted_at IS NOT NULL)
+        AND (NOT $8 OR edited_at IS NOT NULL)
+        ORDER BY created_at DESC LIMIT $9;
+        ",
+        guild_id.0 as i64,
+        author_id,
+        channel_id,
+        after,
+        before,
+        text,
+        deleted_only,
+        edited_only,
+        SEARCH_LIMIT
+    )
+    .fetch_all(pool)
+    .await?;
+
+    if matches.is_empty() {
+        msg.channel_id
+            .say(&ctx.http, "No archived messages match your search.")
+            .await?;
+        return Ok(());
+    }
+
+    let found = if matches.len() as i64 >= SEARCH_LIMIT {
+        format!("{} messages, capped to the newest ones", matches.len())
+    } else {
+        format!("{} messages", matches.len())
+    };
+
+    let status = |edited: bool, deleted: bool| match (edited, deleted) {
+        (true, true) => " (edited, deleted)",
+        (true, false) => " (edited)",
+        (false, true) => " (deleted)",
+        (false, false) => "",
+    };
+
+    if export {
+        let mut content = format!("Found {}.\n", found);
+        for m in &matches {
+            let _ = write!(
+                content,
+                "\n[{}] {} ({}) in {}{}:\n{}\n",
+                m.created_at.format("%F %T UTC"),
+                m.author_tag,
+                m.author_id,
+                m.channel_id,
+                status(m.edited_at.is_some(), m.deleted_at.is_some()),
+                m.content
+            );
+        }
+
+        msg.channel_id
+            .send_files(
+                &ctx.http,
+                vec![text_to_file(
+                    &content,
+                    Some(String::from("search-results.txt")),
+                    false,
+                )],
+                |m| m.content(format!("Found {}.", found)),
+            )
+            .await?;
+
+        return Ok(());
+    }
+
+    let total_pages = matches.chunks(SEARCH_PAGE_SIZE).len();
+    let mut pages = Vec::new();
+    for (i, chunk) in matches.chunks(SEARCH_PAGE_SIZE).enumerate() {
+        let mut description = String::new();
+        for m in chunk {
+            let mut content = m.content.replace('\n', " ");
+            if let Some((end, _)) = content.char_indices().nth(300) {
+                content.truncate(end);
+                content.push_str("...");
+            }
+            if content.is_empty() {
+                content = "*No text*".to_string();
+            }
+
+            let _ = write!(
+                description,
+                "`{}` <@{}> in <#{}>{}\n{}\n\n",
+                m.created_at.format("%F %R"),
+                m.author_id,
+                m.channel_id,
+                status(m.edited_at.is_some(), m.deleted_at.is_some()),
+                content
+            );
+        }
+
+        let mut page = CreateMessage::default();
+        page.embed(|e| {
+            e.title(format!("Search Results ({})", found));
+            e.description(description);
+            e.colour(0x00CDFF);
+            e.footer(|f| {
+                f.text(format!(
+                    "Page {} of {} | Times are in UTC.",
+                    i + 1,
+                    total_pages
+                ));
+
+                f
+            });
+
+            e
+        });
+        pages.push(page);
+    }
+
+    let menu = Menu::new(ctx, msg, pages.as_slice(), MenuOptions::default());
+
+    menu.run().await?;
+
+    Ok(())
+}
+
 /// Shows the message log settings for this server.
 ///
 /// Usage: `[p]log settings`
@@ -387,10 +1138,64 @@ async fn log_settings(ctx: &Context, msg: &Message) -> CommandResult {
         allowed = "No channels".to_string();
     }
 
-    let log_channel_str = match get_channel_from_id(&ctx, guild.id, settings.log_channel_id).await {
-        Ok(c) => format!("Log Channel: {}", c.mention()),
-        Err(_) => String::from("Log channel not set!"),
-    };
+    let mut log_channel_str =
+        match get_channel_from_id(&ctx, guild.id, settings.log_channel_id).await {
+            Ok(c) => format!("Log Channel: {}", c.mention()),
+            Err(_) => String::from("Log channel not set!"),
+        };
+    if settings.archive_on {
+        let _ = write!(
+            log_channel_str,
+            "\nMessage Archive: `On`, kept for {} days",
+            settings.archive_retention
+        );
+    } else {
+        log_channel_str.push_str("\nMessage Archive: `Off`");
+    }
+    if settings.attachments_on {
+        let _ = write!(
+            log_channel_str,
+            "\nSaved Attachments: `On`, up to {} MB, kept for {} days",
+            settings.attachment_max_size, settings.attachment_retention
+        );
+    } else {
+        log_channel_str.push_str("\nSaved Attachments: `Off`");
+    }
+
+    let routes = sqlx::query!(
+        "SELECT event, channel_id FROM log_routes WHERE guild_id = $1;",
+        guild.id.0 as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    // Shows whether each event is logged, and where.
+    let mut events_str = String::new();
+    for event in LogEvent::ALL.iter() {
+        if !settings.logs(*event) {
+            let _ = write!(events_str, "\n{}: `Off`", event.description());
+            continue;
+        }
+
+        if *event == LogEvent::GhostPings && settings.ghost_pings_in_channel {
+            let _ = write!(
+                events_str,
+                "\n{}: Channel of the message",
+                event.description()
+            );
+            continue;
+        }
+
+        let route = routes.iter().find(|r| r.event == event.name());
+        let channel = match route {
+            Some(r) => match get_channel_from_id(ctx, guild.id, Some(r.channel_id)).await {
+                Ok(c) => c.mention().to_string(),
+                Err(_) => "Deleted channel, using default".to_string(),
+            },
+            None => "Default".to_string(),
+        };
+        let _ = write!(events_str, "\n{}: {}", event.description(), channel);
+    }
 
     let whitelist_str = if !whitelist.is_empty() {
         let mut text = String::new();
@@ -423,6 +1228,7 @@ async fn log_settings(ctx: &Context, msg: &Message) -> CommandResult {
                     ("Whitelisted Channels", whitelist_str, false),
                     ("Blacklisted Channels", blacklist_str, false),
                     ("Default Allowed Channels", allowed, false),
+                    ("Events", events_str, false),
                 ]);
 
                 e
@@ -452,6 +1258,15 @@ async fn log_settings(ctx: &Context, msg: &Message) -> CommandResult {
     blacklist_channel,
     remove_whitelist_channel,
     remove_blacklist_channel,
+    log_archive,
+    log_retention,
+    log_attachments,
+    log_file_size,
+    log_file_retention,
+    log_event,
+    log_route,
+    log_ghost_pings,
+    log_search,
     log_settings
 )]
 #[default_command(log_settings)]
@@ -1,9 +1,15 @@
 //! Commands related to TvM's setup are defined here.
 
 use crate::{
-    utils::{checks::*, constants::EMBED_COLOUR, converters::*, database::initialize_tables},
+    board::update_signup_board,
+    template::CycleTemplate,
+    utils::{
+        checks::*, constants::EMBED_COLOUR, converters::*, database::initialize_tables,
+        transaction::Transaction,
+    },
     ConnectionPool,
 };
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use serenity::{
     framework::standard::{
@@ -14,7 +20,7 @@ use serenity::{
     prelude::*,
 };
 use serenity_utils::prompt::yes_or_no_prompt;
-use sqlx::types::Json;
+use sqlx::{types::Json, PgPool};
 use std::fmt::Write;
 use tracing::error;
 
@@ -37,14 +43,40 @@ pub struct Settings {
     pub cycle: Option<Json<Cycle>>,
     pub players: Option<Vec<i64>>,
     pub notify_cooldown: i32,
+    pub signups_autoclose: bool,
+    pub signup_board_id: Option<i64>,
+    pub min_account_age: i32,
+    pub min_member_age: i32,
+    pub signup_role_id: Option<i64>,
+    pub whispers_on: bool,
+    pub whispers_public: bool,
+    pub whisper_limit: i32,
+    pub whisper_log_id: Option<i64>,
+    pub anonymous: bool,
+    pub anon_prefix: String,
+    pub game_category_id: Option<i64>,
+    pub dead_chat_id: Option<i64>,
+    pub dead_see_chats: bool,
+    pub anonymous_links: bool,
+    pub pc_category_ids: Vec<i64>,
+    pub cycle_template: Option<Json<CycleTemplate>>,
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct Cycle {
     pub number: i16,
     pub day: Option<i64>,
     pub night: Option<i64>,
     pub votes: Option<i64>,
+    /// All channels of the cycle, in the order of the cycle template.
+    #[serde(default)]
+    pub channels: Vec<i64>,
+    #[serde(default)]
+    pub night_started: bool,
+    /// The cycle template the cycle was created with. Changes to the template
+    /// are only used from the next cycle.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<CycleTemplate>,
 }
 
 /// Sets the Host role.
@@ -113,11 +145,47 @@ pub async fn dead_role(ctx: &Context, msg: &Message, args: Args) -> CommandResul
     set_role(ctx, msg, args, "Dead", "dead_role_id").await
 }
 
+/// Sets the role members need to sign-up as players or replacements.
+///
+/// **Usage:** `[p]signuprole [role]`
+///
+/// Use the command without a role to let everyone sign-up again.
+///
+/// The bot asks for confirmation before making any changes. This command
+/// cannot be used if the TvM settings are locked.
+#[command("signuprole")]
+#[checks("tvmset_lock")]
+pub async fn signup_role(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    if !args.is_empty() {
+        return set_role(ctx, msg, args, "Sign-up", "signup_role_id").await;
+    }
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    sqlx::query!(
+        "UPDATE config SET signup_role_id = NULL WHERE guild_id = $1;",
+        msg.guild_id.unwrap().0 as i64
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            "Removed the Sign-up role. Anyone can sign-up now.",
+        )
+        .await?;
+
+    Ok(())
+}
+
 /// Automatically creates and sets Host, Player, Specator, Replacement and Dead roles.
 ///
 /// **Usage:** `[p]setroles`
 ///
 /// The bot adds the Host role to the person using the command if it has the permissions.
+/// If one of the roles can't be created, the roles created before it are deleted.
 ///
 /// This command cannot be used if the TvM settings are locked.
 #[command("setroles")]
@@ -129,70 +197,54 @@ pub async fn set_all_roles(ctx: &Context, msg: &Message) -> CommandResult {
         None => return Err(CommandError::from("Couldn't fetch details of this server.")),
     };
 
-    let host_role = match guild
-        .create_role(ctx, |r| {
-            r.hoist(true)
-                .mentionable(true)
-                .name("Hosts")
-                .colour(0xFFBF37)
-        })
-        .await
+    let mut transaction = Transaction::new();
+    let mut roles = Vec::new();
+    for (name, colour) in [
+        ("Hosts", 0xFFBF37),
+        ("Players", 0x37BFFF),
+        ("Replacements", 0x86FF40),
+        ("Spectators", 0xD837FF),
+        ("Dead", 0xDC5757),
+    ]
+    .iter()
     {
-        Ok(r) => r,
-        Err(_) => {
-            msg.channel_id
-                .say(&ctx.http, "Could not create role.")
-                .await?;
-            return Ok(());
+        let res = guild
+            .create_role(ctx, |r| {
+                r.hoist(true).mentionable(true).name(name).colour(*colour)
+            })
+            .await;
+
+        match res {
+            Ok(r) => {
+                transaction.role(&r);
+                roles.push(r);
+            }
+            Err(_) => {
+                let report = transaction.rollback(ctx).await;
+                msg.channel_id
+                    .say(
+                        &ctx.http,
+                        format!("Could not create the `{}` role. {}", name, report),
+                    )
+                    .await?;
+                return Ok(());
+            }
         }
-    };
+    }
+    let (host_role, player_role, repl_role, spec_role, dead_role) =
+        (&roles[0], &roles[1], &roles[2], &roles[3], &roles[4]);
 
     if let Some(m) = guild.members.get(&msg.author.id) {
         // Try to add the role, do nothing if unable to.
         let _ = m.clone().add_role(&ctx.http, host_role.id).await;
     };
 
-    // If we're able to create a role, then we can assume we will be able to create the remaining
-    // four. This is why we let serenity handle the error by using `?`.
-    let player_role = guild
-        .create_role(ctx, |r| {
-            r.hoist(true)
-                .mentionable(true)
-                .name("Players")
-                .colour(0x37BFFF)
-        })
-        .await?;
-    let repl_role = guild
-        .create_role(ctx, |r| {
-            r.hoist(true)
-                .mentionable(true)
-                .name("Replacements")
-                .colour(0x86FF40)
-        })
-        .await?;
-    let spec_role = guild
-        .create_role(ctx, |r| {
-            r.hoist(true)
-                .mentionable(true)
-                .name("Spectators")
-                .colour(0xD837FF)
-        })
-        .await?;
-    let dead_role = guild
-        .create_role(ctx, |r| {
-            r.hoist(true)
-                .mentionable(true)
-                .name("Dead")
-                .colour(0xDC5757)
-        })
-        .await?;
-
     // Put them all in the database.
 
     let data_read = ctx.data.read().await;
     let pool = data_read.get::<ConnectionPool>().unwrap();
 
-    sqlx::query!(
+    let res = sqlx::query!(
         "
         INSERT INTO config (
             guild_id,
@@ -218,7 +270,18 @@ pub async fn set_all_roles(ctx: &Context, msg: &Message) -> CommandResult {
         dead_role.id.0 as i64
     )
     .execute(pool)
-    .await?;
+    .await;
+
+    if res.is_err() {
+        let report = transaction.rollback(ctx).await;
+        msg.channel_id
+            .say(
+                &ctx.http,
+                format!("I couldn't save the roles in the database. {}", report),
+            )
+            .await?;
+        return Ok(());
+    }
 
     match &msg
         .channel_id
@@ -792,84 +855,148 @@ pub async fn notifycd(ctx: &Context, msg: &Message, mut args: Args) -> CommandRe
     Ok(())
 }
 
-/// Opens sign-ups.
+/// Sets the minimum age of a Discord account to sign-up.
 ///
-/// **Usage:** `[p]signopen`
+/// **Usage:** `[p]accountage <hours>`
 ///
-/// Sign-ups are open by default.
+/// The age must be the number of **hours**. It is `0` by default, which
+/// lets any account sign-up.
+///
+/// **Example**
+///
+/// Command: `[p]accountage 168`
+/// Result: Only accounts at least a week old can sign-up.
 ///
 /// This command cannot be used if the TvM settings are locked.
-#[command("signopen")]
+#[command("accountage")]
 #[checks("tvmset_lock")]
-pub async fn sign_open(ctx: &Context, msg: &Message) -> CommandResult {
+pub async fn min_account_age(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let hours: i32 = match args.single() {
+        Ok(i) => i,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, "`hours` must be a valid number.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if hours < 0 {
+        msg.channel_id
+            .say(&ctx.http, "`hours` must be a positive number.")
+            .await?;
+        return Ok(());
+    }
+
     let data_read = ctx.data.read().await;
     let pool = data_read.get::<ConnectionPool>().unwrap();
 
     sqlx::query!(
         "
-        INSERT INTO config(guild_id, signups_on) VALUES($1, $2)
-        ON CONFLICT (guild_id) DO UPDATE SET signups_on = $2;
+        INSERT INTO config(guild_id, min_account_age) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET min_account_age = $2;
         ",
         msg.guild_id.unwrap().0 as i64,
-        true
+        hours
     )
     .execute(pool)
     .await?;
 
-    msg.channel_id.say(&ctx.http, "Opened sign-ups.").await?;
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!("Set the minimum account age to {} hours.", hours),
+        )
+        .await?;
 
     Ok(())
 }
 
-/// Closes sign-ups.
+/// Sets how long someone must be a member of the server to sign-up.
 ///
-/// **Usage:** `[p]signclose`
+/// **Usage:** `[p]memberage <hours>`
 ///
-/// Sign-ups are open by default.
+/// The time must be the number of **hours**. It is `0` by default, which
+/// lets new members sign-up right away.
 ///
 /// This command cannot be used if the TvM settings are locked.
-#[command("signclose")]
+#[command("memberage")]
 #[checks("tvmset_lock")]
-pub async fn sign_close(ctx: &Context, msg: &Message) -> CommandResult {
+pub async fn min_member_age(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let hours: i32 = match args.single() {
+        Ok(i) => i,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, "`hours` must be a valid number.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if hours < 0 {
+        msg.channel_id
+            .say(&ctx.http, "`hours` must be a positive number.")
+            .await?;
+        return Ok(());
+    }
+
     let data_read = ctx.data.read().await;
     let pool = data_read.get::<ConnectionPool>().unwrap();
 
     sqlx::query!(
         "
-        INSERT INTO config(guild_id, signups_on) VALUES($1, $2)
-        ON CONFLICT (guild_id) DO UPDATE SET signups_on = $2;
+        INSERT INTO config(guild_id, min_member_age) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET min_member_age = $2;
         ",
         msg.guild_id.unwrap().0 as i64,
-        false
+        hours
     )
     .execute(pool)
     .await?;
 
-    msg.channel_id.say(&ctx.http, "Closed sign-ups.").await?;
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!("Set the minimum membership time to {} hours.", hours),
+        )
+        .await?;
 
     Ok(())
 }
 
-/// Locks the TvM settings.
+/// Opens sign-ups, either right away or at a scheduled time.
 ///
-/// **Usage:** `[p]lock`
+/// **Usage:** `[p]signopen [date time [timezone]]`
 ///
-/// Commands that can change TvM settings cannot be used after locking.
-/// You can unlock the commands by using `[p]unlock` command.
+/// Sign-ups are open by default.
 ///
-/// **It is recommened to lock the settings before starting the game.**
+/// If you supply a date and time, sign-ups will open at that time and I will
+/// announce it in the sign-ups channel. The date must look like `2021-05-02`
+/// and the time like `18:30`. The timezone must be a name like `Europe/London`.
+/// UTC is used if you don't supply a timezone.
+///
+/// Use `[p]signopen cancel` to cancel a scheduled opening.
+///
+/// **Example**
+///
+/// Command: `[p]signopen 2021-05-02 18:30 America/New_York`
+/// Result: Sign-ups will open at 6:30 PM New York time on 2nd May, 2021.
 ///
 /// This command cannot be used if the TvM settings are locked.
-#[command("lock")]
+#[command("signopen")]
 #[checks("tvmset_lock")]
-pub async fn lock_settings(ctx: &Context, msg: &Message) -> CommandResult {
+pub async fn sign_open(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    if !args.is_empty() {
+        return schedule_signups(ctx, msg, args, "open").await;
+    }
+
     let data_read = ctx.data.read().await;
     let pool = data_read.get::<ConnectionPool>().unwrap();
 
     sqlx::query!(
         "
-        INSERT INTO config(guild_id, tvmset_lock) VALUES($1, $2)
-        ON CONFLICT (guild_id) DO UPDATE SET tvmset_lock = $2;
+        INSERT INTO config(guild_id, signups_on) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET signups_on = $2;
         ",
         msg.guild_id.unwrap().0 as i64,
         true
@@ -877,28 +1004,38 @@ pub async fn lock_settings(ctx: &Context, msg: &Message) -> CommandResult {
     .execute(pool)
     .await?;
 
-    msg.channel_id
-        .say(&ctx.http, "Locked TvM settings.")
-        .await?;
+    msg.channel_id.say(&ctx.http, "Opened sign-ups.").await?;
 
     Ok(())
 }
 
-/// Unlocks the TvM settings.
+/// Closes sign-ups, either right away or at a scheduled time.
 ///
-/// **Usage:** `[p]unlock`
+/// **Usage:** `[p]signclose [date time [timezone]]`
 ///
-/// Commands are *unlocked* by default.
-#[command("unlock")]
-#[description = "Unlocks TvM settings configuration."]
-pub async fn unlock_settings(ctx: &Context, msg: &Message) -> CommandResult {
+/// Sign-ups are open by default.
+///
+/// If you supply a date and time, sign-ups will close at that time and I will
+/// announce it in the sign-ups channel. The format is the same as for the
+/// `[p]signopen` command.
+///
+/// Use `[p]signclose cancel` to cancel a scheduled closing.
+///
+/// This command cannot be used if the TvM settings are locked.
+#[command("signclose")]
+#[checks("tvmset_lock")]
+pub async fn sign_close(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    if !args.is_empty() {
+        return schedule_signups(ctx, msg, args, "close").await;
+    }
+
     let data_read = ctx.data.read().await;
     let pool = data_read.get::<ConnectionPool>().unwrap();
 
     sqlx::query!(
         "
-        INSERT INTO config(guild_id, tvmset_lock) VALUES($1, $2)
-        ON CONFLICT (guild_id) DO UPDATE SET tvmset_lock = $2;
+        INSERT INTO config(guild_id, signups_on) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET signups_on = $2;
         ",
         msg.guild_id.unwrap().0 as i64,
         false
@@ -906,90 +1043,870 @@ pub async fn unlock_settings(ctx: &Context, msg: &Message) -> CommandResult {
     .execute(pool)
     .await?;
 
-    msg.channel_id
-        .say(&ctx.http, "Unlocked TvM settings.")
-        .await?;
+    msg.channel_id.say(&ctx.http, "Closed sign-ups.").await?;
 
     Ok(())
 }
 
-/// Shows the TvM settings.
-///
-/// **Usage:** `[p]settings`
-///
-/// **Alias:** `show`
-///
-/// Embed Links permission is required for this command to work.
-#[command("settings")]
-#[aliases("show")]
-pub async fn tvm_settings(ctx: &Context, msg: &Message) -> CommandResult {
-    let guild = match msg.guild(ctx).await {
-        Some(i) => i,
-        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
-    };
+async fn schedule_signups(
+    ctx: &Context,
+    msg: &Message,
+    mut args: Args,
+    action: &str,
+) -> CommandResult {
+    let guild_id = msg.guild_id.unwrap().0 as i64;
 
     let data_read = ctx.data.read().await;
     let pool = data_read.get::<ConnectionPool>().unwrap();
 
-    let settings: Settings = match sqlx::query_as_unchecked!(
-        Settings,
-        "
-        SELECT * FROM config WHERE guild_id = $1;
-        ",
-        guild.id.0 as i64
-    )
-    .fetch_one(pool)
-    .await
-    {
-        Ok(s) => s,
+    // `args` isn't empty, so there is at least one argument.
+    let date = args.single::<String>()?;
+
+    if date.eq_ignore_ascii_case("cancel") {
+        sqlx::query!(
+            "DELETE FROM signup_schedule WHERE guild_id = $1 AND action = $2;",
+            guild_id,
+            action
+        )
+        .execute(pool)
+        .await?;
+
+        msg.channel_id
+            .say(
+                &ctx.http,
+                format!("Cancelled the scheduled sign-ups {}.", action),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    let time = match args.single::<String>() {
+        Ok(t) => t,
         Err(_) => {
-            error!(
-                "config table wasn't initialized for guild with ID {}.",
-                guild.id.0 as i64
-            );
-            // Initialize all three tables.
-            initialize_tables(&ctx, &guild).await;
+            msg.channel_id
+                .say(&ctx.http, "I need both a date and a time.")
+                .await?;
             return Ok(());
         }
     };
+    let timezone = args.single::<String>().ok();
 
-    let mut fields = Vec::new();
+    let run_at = match parse_datetime(&date, &time, timezone.as_deref()) {
+        Ok(d) => d,
+        Err(e) => {
+            msg.channel_id.say(&ctx.http, e).await?;
+            return Ok(());
+        }
+    };
 
-    let mut roles_str = String::new();
-    match settings.host_role_id {
-        Some(id) => write!(roles_str, "\nHost Role: <@&{}>", id),
-        None => write!(roles_str, "\nHost Role: `Not set`"),
-    }?;
-    match settings.player_role_id {
-        Some(id) => write!(roles_str, "\nPlayer Role: <@&{}>", id),
-        None => write!(roles_str, "\nPlayer Role: `Not set`"),
-    }?;
-    match settings.spec_role_id {
-        Some(id) => write!(roles_str, "\nSpectator Role: <@&{}>", id),
-        None => write!(roles_str, "\nSpectator Role: `Not set`"),
-    }?;
-    match settings.repl_role_id {
-        Some(id) => write!(roles_str, "\nReplacement Role: <@&{}>", id),
-        None => write!(roles_str, "\nReplacement Role: `Not set`"),
-    }?;
-    match settings.dead_role_id {
-        Some(id) => write!(roles_str, "\nDead Player Role: <@&{}>", id),
-        None => write!(roles_str, "\nDead Player Role: `Not set`"),
-    }?;
+    if run_at <= Utc::now() {
+        msg.channel_id
+            .say(&ctx.http, "That time has already passed.")
+            .await?;
+        return Ok(());
+    }
 
-    fields.push(("**Roles**", roles_str.trim(), false));
+    sqlx::query!(
+        "
+        INSERT INTO signup_schedule(guild_id, action, run_at) VALUES($1, $2, $3)
+        ON CONFLICT (guild_id, action) DO UPDATE SET run_at = $3;
+        ",
+        guild_id,
+        action,
+        run_at
+    )
+    .execute(pool)
+    .await?;
 
-    let mut channels_str = String::new();
-    match settings.signups_channel_id {
-        Some(id) => write!(channels_str, "\nSign-ups Channel: <#{}>", id),
-        None => write!(channels_str, "\nSign-ups Channel: `Not set`"),
-    }?;
-    match settings.na_channel_id {
-        Some(id) => write!(channels_str, "\nNight Actions Channel: <#{}>", id),
-        None => write!(channels_str, "\nNight Actions Channel: `Not set`"),
-    }?;
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!(
+                "Sign-ups will {} on {}.",
+                action,
+                run_at.format("%B %-d, %Y at %H:%M UTC")
+            ),
+        )
+        .await?;
 
-    fields.push(("**Channels**", channels_str.trim(), false));
+    Ok(())
+}
+
+/// Opens or closes sign-ups and announces it in the sign-ups channel.
+///
+/// This is used when there is no command message to reply to, like for
+/// scheduled sign-ups and when sign-ups close automatically.
+pub(crate) async fn set_signups(
+    ctx: &Context,
+    pool: &PgPool,
+    guild_id: GuildId,
+    open: bool,
+    announcement: &str,
+) -> Result<(), sqlx::Error> {
+    let res = sqlx::query!(
+        "
+        INSERT INTO config(guild_id, signups_on) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET signups_on = $2
+        RETURNING signups_channel_id;
+        ",
+        guild_id.0 as i64,
+        open
+    )
+    .fetch_one(pool)
+    .await?;
+
+    if let Ok(channel) = get_channel_from_id(ctx, guild_id, res.signups_channel_id).await {
+        if let Err(why) = channel.say(&ctx.http, announcement).await {
+            error!("Unable to announce sign-ups change: {}", why);
+        }
+    }
+
+    update_signup_board(ctx, guild_id).await;
+
+    Ok(())
+}
+
+/// Toggles the `Auto-close Sign-ups` setting. It is `false` by default.
+///
+/// **Usage:** `[p]autoclose [setting]`
+///
+/// When enabled, sign-ups close automatically once the maximum number of
+/// players have signed up. I announce it in the sign-ups channel.
+///
+/// You can optionally specify the setting to use. `setting` can be one of
+/// - `true`
+/// - `false`
+///
+/// This command cannot be used if the TvM settings are locked.
+#[command("autoclose")]
+#[checks("tvmset_lock")]
+pub async fn signups_autoclose(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let mut toggle = false;
+    let mut setting = false;
+
+    if args.is_empty() {
+        toggle = true;
+    } else {
+        setting = match args.single() {
+            Ok(s) => s,
+            Err(_) => return Err(CommandError::from("Invalid option.")),
+        };
+    }
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    if toggle {
+        if let Ok(r) = sqlx::query!(
+            "SELECT signups_autoclose FROM config WHERE guild_id = $1",
+            msg.guild_id.unwrap().0 as i64
+        )
+        .fetch_one(pool)
+        .await
+        {
+            setting = !r.signups_autoclose;
+        }
+    }
+
+    sqlx::query!(
+        "
+        INSERT INTO config(guild_id, signups_autoclose) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET signups_autoclose = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        setting
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!("Set `Auto-close Sign-ups` to {}.", setting),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Toggles the `Whispers` setting. It is `false` by default.
+///
+/// **Usage:** `[p]whispers [setting]`
+///
+/// When enabled, players can whisper to each other with the `whisper` command.
+///
+/// You can optionally specify the setting to use. `setting` can be one of
+/// - `true`
+/// - `false`
+///
+/// This command cannot be used if the TvM settings are locked.
+#[command("whispers")]
+#[checks("tvmset_lock")]
+pub async fn whispers_on(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let mut toggle = false;
+    let mut setting = false;
+
+    if args.is_empty() {
+        toggle = true;
+    } else {
+        setting = match args.single() {
+            Ok(s) => s,
+            Err(_) => return Err(CommandError::from("Invalid option.")),
+        };
+    }
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    if toggle {
+        if let Ok(r) = sqlx::query!(
+            "SELECT whispers_on FROM config WHERE guild_id = $1",
+            msg.guild_id.unwrap().0 as i64
+        )
+        .fetch_one(pool)
+        .await
+        {
+            setting = !r.whispers_on;
+        }
+    }
+
+    sqlx::query!(
+        "
+        INSERT INTO config(guild_id, whispers_on) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET whispers_on = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        setting
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(&ctx.http, format!("Set `Whispers` to {}.", setting))
+        .await?;
+
+    Ok(())
+}
+
+/// Toggles the `Public Whispers` setting. It is `false` by default.
+///
+/// **Usage:** `[p]publicwhispers [setting]`
+///
+/// When enabled, I tell everyone in the day channel who whispered to whom.
+/// The contents of whispers are never made public.
+///
+/// You can optionally specify the setting to use. `setting` can be one of
+/// - `true`
+/// - `false`
+///
+/// This command cannot be used if the TvM settings are locked.
+#[command("publicwhispers")]
+#[checks("tvmset_lock")]
+pub async fn whispers_public(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let mut toggle = false;
+    let mut setting = false;
+
+    if args.is_empty() {
+        toggle = true;
+    } else {
+        setting = match args.single() {
+            Ok(s) => s,
+            Err(_) => return Err(CommandError::from("Invalid option.")),
+        };
+    }
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    if toggle {
+        if let Ok(r) = sqlx::query!(
+            "SELECT whispers_public FROM config WHERE guild_id = $1",
+            msg.guild_id.unwrap().0 as i64
+        )
+        .fetch_one(pool)
+        .await
+        {
+            setting = !r.whispers_public;
+        }
+    }
+
+    sqlx::query!(
+        "
+        INSERT INTO config(guild_id, whispers_public) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET whispers_public = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        setting
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(&ctx.http, format!("Set `Public Whispers` to {}.", setting))
+        .await?;
+
+    Ok(())
+}
+
+/// Sets the number of whispers a player can send each day.
+///
+/// **Usage:** `[p]whisperlimit <number>`
+///
+/// The limit is `0` by default, which means there is no limit.
+///
+/// This command cannot be used if the TvM settings are locked.
+#[command("whisperlimit")]
+#[checks("tvmset_lock")]
+pub async fn whisper_limit(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let limit: i32 = match args.single() {
+        Ok(i) => i,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, "`number` must be a valid number.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if limit < 0 {
+        msg.channel_id
+            .say(&ctx.http, "`number` must be a positive number.")
+            .await?;
+        return Ok(());
+    }
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    sqlx::query!(
+        "
+        INSERT INTO config(guild_id, whisper_limit) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET whisper_limit = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        limit
+    )
+    .execute(pool)
+    .await?;
+
+    let reply = if limit == 0 {
+        String::from("Removed the limit on whispers.")
+    } else {
+        format!("Players can now send {} whispers each day.", limit)
+    };
+    msg.channel_id.say(&ctx.http, reply).await?;
+
+    Ok(())
+}
+
+/// Toggles the `Anonymous Mode` setting. It is `false` by default.
+///
+/// **Usage:** `[p]anonymous [setting]`
+///
+/// When enabled, players can talk in the day and voting channels under their
+/// alias. They post in their private chat with the anonymous prefix, like
+/// `!day Hello` or `!vote VTL Blue Fox`, and I re-post the message. Hosts can
+/// set aliases with the `alias` command.
+///
+/// You can optionally specify the setting to use. `setting` can be one of
+/// - `true`
+/// - `false`
+///
+/// This command cannot be used if the TvM settings are locked.
+#[command("anonymous")]
+#[checks("tvmset_lock")]
+pub async fn anonymous_mode(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let mut toggle = false;
+    let mut setting = false;
+
+    if args.is_empty() {
+        toggle = true;
+    } else {
+        setting = match args.single() {
+            Ok(s) => s,
+            Err(_) => return Err(CommandError::from("Invalid option.")),
+        };
+    }
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    if toggle {
+        if let Ok(r) = sqlx::query!(
+            "SELECT anonymous FROM config WHERE guild_id = $1",
+            msg.guild_id.unwrap().0 as i64
+        )
+        .fetch_one(pool)
+        .await
+        {
+            setting = !r.anonymous;
+        }
+    }
+
+    sqlx::query!(
+        "
+        INSERT INTO config(guild_id, anonymous) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET anonymous = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        setting
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(&ctx.http, format!("Set `Anonymous Mode` to {}.", setting))
+        .await?;
+
+    Ok(())
+}
+
+/// Sets the prefix players use to talk under their alias.
+///
+/// **Usage:** `[p]anonprefix <prefix>`
+///
+/// The prefix is `!` by default.
+///
+/// This command cannot be used if the TvM settings are locked.
+#[command("anonprefix")]
+#[checks("tvmset_lock")]
+#[min_args(1)]
+pub async fn anon_prefix(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let prefix = args.single::<String>()?;
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    sqlx::query!(
+        "
+        INSERT INTO config(guild_id, anon_prefix) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET anon_prefix = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        prefix
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!("Set the anonymous prefix to `{}`.", prefix),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Sets the template the `cycle` command creates channels from.
+///
+/// **Usage:** `[p]cycletemplate [template | reset]`
+///
+/// The template is written in JSON. It has the name of the category and a
+/// list of channels. For every channel, you can set
+/// - `name`: the name of the channel
+/// - `topic`: the topic of the channel
+/// - `slowmode`: the slowmode of the channel, in seconds
+/// - `use`: what the bot uses the channel for, any of `day`, `votes` and `night`
+/// - `day` and `night`: whether everyone can see the channel during that phase,
+/// and which of `hosts`, `players`, `spectators`, `replacements` and `dead` can
+/// talk in it
+///
+/// `{n}` in the names and topics is replaced by the cycle number. Without a
+/// template, the bot shows the one currently used. `reset` goes back to the
+/// default template.
+///
+/// This command cannot be used if the TvM settings are locked.
+///
+/// **Example**
+///
+/// Command:
+/// ```
+/// [p]cycletemplate {"category": "Day {n}", "channels": [
+///   {"name": "d{n}-discussion", "use": ["day", "votes"], "slowmode": 5,
+///    "day": {"visible": true, "write": ["players"]}, "night": {"visible": true}},
+///   {"name": "n{n}", "use": ["night"], "night": {"visible": true, "write": ["players"]}}
+/// ]}
+/// ```
+#[command("cycletemplate")]
+#[checks("tvmset_lock")]
+pub async fn cycle_template(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    let guild_id = match msg.guild_id {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let input = args.rest().trim();
+    if input.is_empty() {
+        let template = CycleTemplate::get(pool, guild_id).await;
+        msg.channel_id
+            .say(
+                &ctx.http,
+                format!("```json\n{}\n```", serde_json::to_string_pretty(&template)?),
+            )
+            .await?;
+        return Ok(());
+    }
+
+    if input.eq_ignore_ascii_case("reset") {
+        sqlx::query!(
+            "UPDATE config SET cycle_template = NULL WHERE guild_id = $1;",
+            guild_id.0 as i64
+        )
+        .execute(pool)
+        .await?;
+
+        msg.channel_id
+            .say(&ctx.http, "Set the cycle template back to the default one.")
+            .await?;
+        return Ok(());
+    }
+
+    // Allow the template to be put in a code block.
+    let input = input.trim_start_matches("```json").trim_matches('`').trim();
+
+    let template: CycleTemplate = match serde_json::from_str(input) {
+        Ok(t) => t,
+        Err(e) => {
+            msg.channel_id
+                .say(&ctx.http, format!("The template is invalid: {}", e))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if let Err(e) = template.validate() {
+        msg.channel_id.say(&ctx.http, e).await?;
+        return Ok(());
+    }
+
+    sqlx::query!(
+        "
+        INSERT INTO config(guild_id, cycle_template) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET cycle_template = $2;
+        ",
+        guild_id.0 as i64,
+        serde_json::to_value(&template)?
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            "Set the cycle template. It will be used from the next cycle.",
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Toggles the `Dead Can See Chats` setting. It is `false` by default.
+///
+/// **Usage:** `[p]deadview [setting]`
+///
+/// When enabled, players who are killed with the `kill` command can read,
+/// but not talk in, the current night channel and all faction chats. Night
+/// channels and faction chats created later can be read by the dead player
+/// role. The `revive` command takes the view away again.
+///
+/// You can optionally specify the setting to use. `setting` can be one of
+/// - `true`
+/// - `false`
+///
+/// This command cannot be used if the TvM settings are locked.
+#[command("deadview")]
+#[checks("tvmset_lock")]
+pub async fn dead_see_chats(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let mut toggle = false;
+    let mut setting = false;
+
+    if args.is_empty() {
+        toggle = true;
+    } else {
+        setting = match args.single() {
+            Ok(s) => s,
+            Err(_) => return Err(CommandError::from("Invalid option.")),
+        };
+    }
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    if toggle {
+        if let Ok(r) = sqlx::query!(
+            "SELECT dead_see_chats FROM config WHERE guild_id = $1",
+            msg.guild_id.unwrap().0 as i64
+        )
+        .fetch_one(pool)
+        .await
+        {
+            setting = !r.dead_see_chats;
+        }
+    }
+
+    sqlx::query!(
+        "
+        INSERT INTO config(guild_id, dead_see_chats) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET dead_see_chats = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        setting
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(
+            &ctx.http,
+            format!("Set `Dead Can See Chats` to {}.", setting),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Toggles the `Anonymous Links` setting. It is `true` by default.
+///
+/// **Usage:** `[p]anonlinks [setting]`
+///
+/// When enabled, messages copied between channels linked with the `link`
+/// command show "Anonymous" instead of the author's name.
+///
+/// You can optionally specify the setting to use. `setting` can be one of
+/// - `true`
+/// - `false`
+///
+/// This command cannot be used if the TvM settings are locked.
+#[command("anonlinks")]
+#[checks("tvmset_lock")]
+pub async fn anonymous_links(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let mut toggle = false;
+    let mut setting = false;
+
+    if args.is_empty() {
+        toggle = true;
+    } else {
+        setting = match args.single() {
+            Ok(s) => s,
+            Err(_) => return Err(CommandError::from("Invalid option.")),
+        };
+    }
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    if toggle {
+        if let Ok(r) = sqlx::query!(
+            "SELECT anonymous_links FROM config WHERE guild_id = $1",
+            msg.guild_id.unwrap().0 as i64
+        )
+        .fetch_one(pool)
+        .await
+        {
+            setting = !r.anonymous_links;
+        }
+    }
+
+    sqlx::query!(
+        "
+        INSERT INTO config(guild_id, anonymous_links) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET anonymous_links = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        setting
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(&ctx.http, format!("Set `Anonymous Links` to {}.", setting))
+        .await?;
+
+    Ok(())
+}
+
+/// Locks the TvM settings.
+///
+/// **Usage:** `[p]lock`
+///
+/// Commands that can change TvM settings cannot be used after locking.
+/// You can unlock the commands by using `[p]unlock` command.
+///
+/// **It is recommened to lock the settings before starting the game.**
+///
+/// This command cannot be used if the TvM settings are locked.
+#[command("lock")]
+#[checks("tvmset_lock")]
+pub async fn lock_settings(ctx: &Context, msg: &Message) -> CommandResult {
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    sqlx::query!(
+        "
+        INSERT INTO config(guild_id, tvmset_lock) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET tvmset_lock = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        true
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(&ctx.http, "Locked TvM settings.")
+        .await?;
+
+    Ok(())
+}
+
+/// Unlocks the TvM settings.
+///
+/// **Usage:** `[p]unlock`
+///
+/// Commands are *unlocked* by default.
+#[command("unlock")]
+#[description = "Unlocks TvM settings configuration."]
+pub async fn unlock_settings(ctx: &Context, msg: &Message) -> CommandResult {
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    sqlx::query!(
+        "
+        INSERT INTO config(guild_id, tvmset_lock) VALUES($1, $2)
+        ON CONFLICT (guild_id) DO UPDATE SET tvmset_lock = $2;
+        ",
+        msg.guild_id.unwrap().0 as i64,
+        false
+    )
+    .execute(pool)
+    .await?;
+
+    msg.channel_id
+        .say(&ctx.http, "Unlocked TvM settings.")
+        .await?;
+
+    Ok(())
+}
+
+/// Shows the TvM settings.
+///
+/// **Usage:** `[p]settings`
+///
+/// **Alias:** `show`
+///
+/// Embed Links permission is required for this command to work.
+#[command("settings")]
+#[aliases("show")]
+pub async fn tvm_settings(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let settings: Settings = match sqlx::query_as_unchecked!(
+        Settings,
+        "
+        SELECT * FROM config WHERE guild_id = $1;
+        ",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(s) => s,
+        Err(_) => {
+            error!(
+                "config table wasn't initialized for guild with ID {}.",
+                guild.id.0 as i64
+            );
+            // Initialize all three tables.
+            initialize_tables(&ctx, &guild).await;
+            return Ok(());
+        }
+    };
+
+    let mut fields = Vec::new();
+
+    let mut roles_str = String::new();
+    match settings.host_role_id {
+        Some(id) => write!(roles_str, "\nHost Role: <@&{}>", id),
+        None => write!(roles_str, "\nHost Role: `Not set`"),
+    }?;
+    match settings.player_role_id {
+        Some(id) => write!(roles_str, "\nPlayer Role: <@&{}>", id),
+        None => write!(roles_str, "\nPlayer Role: `Not set`"),
+    }?;
+    match settings.spec_role_id {
+        Some(id) => write!(roles_str, "\nSpectator Role: <@&{}>", id),
+        None => write!(roles_str, "\nSpectator Role: `Not set`"),
+    }?;
+    match settings.repl_role_id {
+        Some(id) => write!(roles_str, "\nReplacement Role: <@&{}>", id),
+        None => write!(roles_str, "\nReplacement Role: `Not set`"),
+    }?;
+    match settings.dead_role_id {
+        Some(id) => write!(roles_str, "\nDead Player Role: <@&{}>", id),
+        None => write!(roles_str, "\nDead Player Role: `Not set`"),
+    }?;
+    match settings.signup_role_id {
+        Some(id) => write!(roles_str, "\nSign-up Role: <@&{}>", id),
+        None => write!(roles_str, "\nSign-up Role: `Not set`"),
+    }?;
+
+    fields.push(("**Roles**", roles_str.trim(), false));
+
+    let mut channels_str = String::new();
+    match settings.signups_channel_id {
+        Some(id) => write!(channels_str, "\nSign-ups Channel: <#{}>", id),
+        None => write!(channels_str, "\nSign-ups Channel: `Not set`"),
+    }?;
+    match settings.na_channel_id {
+        Some(id) => write!(channels_str, "\nNight Actions Channel: <#{}>", id),
+        None => write!(channels_str, "\nNight Actions Channel: `Not set`"),
+    }?;
+    match settings.game_category_id {
+        Some(id) => write!(channels_str, "\nGame Category: <#{}>", id),
+        None => write!(channels_str, "\nGame Category: `Not set`"),
+    }?;
+    match settings.dead_chat_id {
+        Some(id) => write!(channels_str, "\nDead Chat: <#{}>", id),
+        None => write!(channels_str, "\nDead Chat: `Not set`"),
+    }?;
+    match settings.whisper_log_id {
+        Some(id) => write!(channels_str, "\nWhisper Log Channel: <#{}>", id),
+        None => write!(channels_str, "\nWhisper Log Channel: `Not set`"),
+    }?;
+    match (settings.signups_channel_id, settings.signup_board_id) {
+        (Some(channel_id), Some(id)) => write!(
+            channels_str,
+            "\nSign-up Board: [Jump](https://discord.com/channels/{}/{}/{})",
+            guild.id.0, channel_id, id
+        ),
+        _ => write!(channels_str, "\nSign-up Board: `Not posted`"),
+    }?;
+    if settings.pc_category_ids.is_empty() {
+        write!(channels_str, "\nPrivate Chat Categories: `None`")?;
+    } else {
+        let categories: Vec<String> = settings
+            .pc_category_ids
+            .iter()
+            .map(|id| format!("<#{}>", id))
+            .collect();
+        write!(
+            channels_str,
+            "\nPrivate Chat Categories: {}",
+            categories.join(", ")
+        )?;
+    }
+
+    fields.push(("**Channels**", channels_str.trim(), false));
 
     let mut misc_str = String::new();
     match settings.tvmset_lock {
@@ -1014,6 +1931,69 @@ pub async fn tvm_settings(ctx: &Context, msg: &Message) -> CommandResult {
         "\nNotify Cooldown: `{}`",
         settings.notify_cooldown
     )?;
+    write!(
+        misc_str,
+        "\nAuto-close Sign-ups: `{}`",
+        settings.signups_autoclose
+    )?;
+    write!(
+        misc_str,
+        "\nMinimum Account Age: `{} hours`",
+        settings.min_account_age
+    )?;
+    write!(
+        misc_str,
+        "\nMinimum Membership Time: `{} hours`",
+        settings.min_member_age
+    )?;
+    write!(misc_str, "\nWhispers: `{}`", settings.whispers_on)?;
+    write!(
+        misc_str,
+        "\nPublic Whispers: `{}`",
+        settings.whispers_public
+    )?;
+    write!(
+        misc_str,
+        "\nDead Can See Chats: `{}`",
+        settings.dead_see_chats
+    )?;
+    write!(
+        misc_str,
+        "\nAnonymous Links: `{}`",
+        settings.anonymous_links
+    )?;
+    let template = if settings.cycle_template.is_some() {
+        "Custom"
+    } else {
+        "Default"
+    };
+    write!(misc_str, "\nCycle Template: `{}`", template)?;
+    match settings.whisper_limit {
+        0 => write!(misc_str, "\nWhisper Limit: `None`"),
+        l => write!(misc_str, "\nWhisper Limit: `{} per day`", l),
+    }?;
+    write!(misc_str, "\nAnonymous Mode: `{}`", settings.anonymous)?;
+    write!(misc_str, "\nAnonymous Prefix: `{}`", settings.anon_prefix)?;
+
+    let schedule = sqlx::query!(
+        "SELECT action, run_at FROM signup_schedule WHERE guild_id = $1 ORDER BY run_at;",
+        guild.id.0 as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for row in schedule {
+        write!(
+            misc_str,
+            "\nSign-ups {}: `{}`",
+            if row.action == "open" {
+                "Open On"
+            } else {
+                "Close On"
+            },
+            row.run_at.format("%B %-d, %Y at %H:%M UTC")
+        )?;
+    }
 
     fields.push(("**Miscellaneous**", misc_str.trim(), false));
 
@@ -1044,12 +2024,24 @@ pub async fn tvm_settings(ctx: &Context, msg: &Message) -> CommandResult {
     spectator_role,
     repl_role,
     dead_role,
+    signup_role,
     na_channel,
     signups_channel,
     can_change_na,
     total_players,
     sign_open,
     sign_close,
+    signups_autoclose,
+    min_account_age,
+    min_member_age,
+    whispers_on,
+    whispers_public,
+    whisper_limit,
+    anonymous_mode,
+    anon_prefix,
+    dead_see_chats,
+    anonymous_links,
+    cycle_template,
     lock_settings,
     unlock_settings,
     tvm_settings,
@@ -2,10 +2,12 @@
 //! users, like players, spectators and replacements.
 
 use crate::{
+    board::update_signup_board,
     commands::{
-        host::{get_na_channel, CycleContainer, Data},
-        setup::Cycle,
+        host::{get_na_channel, get_whisper_log, CycleContainer, Data},
+        setup::{set_signups, Cycle},
     },
+    relay::get_webhook,
     utils::{
         constants::EMBED_COLOUR,
         converters::{get_channel, get_channel_from_id, get_member, get_role, to_channel, to_role},
@@ -28,13 +30,14 @@ use serenity::{
     model::{
         misc::Mentionable,
         prelude::{
-            Guild, GuildChannel, GuildId, Member, Message, PermissionOverwriteType, Role, User,
+            ChannelId, Guild, GuildChannel, GuildId, Member, Message, MessageId,
+            PermissionOverwriteType, Role, RoleId, User, UserId,
         },
     },
     prelude::Context,
     utils::{content_safe, ContentSafeOptions},
 };
-use sqlx::types::Json;
+use sqlx::{types::Json, PgPool};
 use std::{
     borrow::Cow,
     collections::{HashMap, HashSet},
@@ -52,6 +55,10 @@ struct SignSettings {
     player_role_id: Option<i64>,
     spec_role_id: Option<i64>,
     repl_role_id: Option<i64>,
+    signups_autoclose: bool,
+    min_account_age: i32,
+    min_member_age: i32,
+    signup_role_id: Option<i64>,
 }
 
 enum Roles {
@@ -61,7 +68,7 @@ enum Roles {
 }
 
 #[derive(Debug, PartialEq, Eq, Hash)]
-enum Vote {
+pub(crate) enum Vote {
     Vtl(String),
     UnVtl(String),
     Vtnl,
@@ -71,16 +78,25 @@ struct VoteData {
     pub(crate) player_role_id: Option<i64>,
     pub(crate) cycle: Option<Json<Cycle>>,
     pub(crate) players: Option<Vec<i64>>,
+    pub(crate) anonymous: bool,
 }
 
 /// Sign-in for the TvM.
 ///
-/// **Usage:** `[p]in`
+/// **Usage:** `[p]in [preferences]`
 ///
 /// The command must be used in the sign-ups channel. It cannot be used
 /// once the game has started.
+///
+/// You can optionally tell the hosts which roles or factions you'd like to
+/// get, separated by commas. Start a preference with `prefer` or `no`.
+/// Anything else you type after the command is ignored.
+///
+/// **Example**
+///
+/// Command: `[p]in prefer Town, no Jester`
 #[command("in")]
-async fn sign_in(ctx: &Context, msg: &Message) -> CommandResult {
+async fn sign_in(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     let guild = match msg.guild(ctx).await {
         Some(i) => i,
         None => return Err(CommandError::from("Couldn't fetch details of this server.")),
@@ -103,12 +119,15 @@ async fn sign_in(ctx: &Context, msg: &Message) -> CommandResult {
         }
     };
 
-    if !(settings.total_players.unwrap_or(12) > settings.total_signups.unwrap_or(0)) {
+    let total_players = settings.total_players.unwrap_or(12);
+    let total_signups = settings.total_signups.unwrap_or(0);
+    if !(total_players > total_signups) {
         msg.channel_id
             .say(&ctx.http, "Maximum allowed players already signed up.")
             .await?;
         return Ok(());
     }
+    let autoclose = settings.signups_autoclose;
 
     let signups_channel = match get_signups_channel(&ctx, &guild, settings.signups_channel_id).await
     {
@@ -129,6 +148,11 @@ async fn sign_in(ctx: &Context, msg: &Message) -> CommandResult {
         return Ok(());
     }
 
+    if let Err(e) = check_eligibility(ctx, msg, &guild, &settings).await {
+        msg.channel_id.say(&ctx.http, e).await?;
+        return Ok(());
+    }
+
     // All first checks have been passed. We'll check if Player role exists now.
     let role = match get_role(ctx, guild.id, settings.player_role_id).await {
         Ok(r) => r,
@@ -182,6 +206,35 @@ async fn sign_in(ctx: &Context, msg: &Message) -> CommandResult {
     .execute(pool)
     .await?;
 
+    let (prefer, avoid) = parse_preferences(args.rest());
+    if !(prefer.is_empty() && avoid.is_empty()) {
+        sqlx::query!(
+            "
+            INSERT INTO signup_prefs (guild_id, user_id, prefer, avoid) VALUES ($1, $2, $3, $4)
+            ON CONFLICT (guild_id, user_id) DO UPDATE SET prefer = $3, avoid = $4;
+            ",
+            guild.id.0 as i64,
+            member.user.id.0 as i64,
+            &prefer,
+            &avoid
+        )
+        .execute(pool)
+        .await?;
+    }
+
+    if autoclose && total_signups + 1 >= total_players {
+        set_signups(
+            ctx,
+            pool,
+            guild.id,
+            false,
+            "**Sign-ups are now closed.** The maximum number of players have signed up.",
+        )
+        .await?;
+    }
+
+    update_signup_board(ctx, guild.id).await;
+
     Ok(())
 }
 
@@ -273,6 +326,8 @@ async fn sign_out(ctx: &Context, msg: &Message) -> CommandResult {
         }
     };
 
+    update_signup_board(ctx, guild.id).await;
+
     Ok(())
 }
 
@@ -329,6 +384,11 @@ async fn sign_repl(ctx: &Context, msg: &Message) -> CommandResult {
         return Ok(());
     }
 
+    if let Err(e) = check_eligibility(ctx, msg, &guild, &settings).await {
+        msg.channel_id.say(&ctx.http, e).await?;
+        return Ok(());
+    }
+
     // All first checks have been passed. We'll check if Spectator role exists now.
     let role = match get_role(ctx, guild.id, settings.repl_role_id).await {
         Ok(r) => r,
@@ -357,6 +417,23 @@ async fn sign_repl(ctx: &Context, msg: &Message) -> CommandResult {
     )
     .await?;
 
+    // Replacements are promoted in the order they signed up in.
+    {
+        let data_read = ctx.data.read().await;
+        let pool = data_read.get::<ConnectionPool>().unwrap();
+
+        sqlx::query!(
+            "
+            INSERT INTO replacement_signups(guild_id, user_id) VALUES($1, $2)
+            ON CONFLICT (guild_id, user_id) DO UPDATE SET created_at = now();
+            ",
+            guild.id.0 as i64,
+            msg.author.id.0 as i64
+        )
+        .execute(pool)
+        .await?;
+    }
+
     match &msg.react(&ctx.http, '✅').await {
         Ok(_) => (),
         Err(_) => {
@@ -366,6 +443,97 @@ async fn sign_repl(ctx: &Context, msg: &Message) -> CommandResult {
         }
     };
 
+    update_signup_board(ctx, guild.id).await;
+
+    Ok(())
+}
+
+/// Parses sign-up preferences like `prefer Town, no Jester`.
+///
+/// Returns the roles or factions that are preferred and the ones to avoid.
+fn parse_preferences(text: &str) -> (Vec<String>, Vec<String>) {
+    let mut prefer = Vec::new();
+    let mut avoid = Vec::new();
+
+    for clause in text.split(&[',', ';'][..]) {
+        let clause = clause.trim();
+        let starts_with =
+            |p: &str| matches!(clause.get(..p.len()), Some(s) if s.eq_ignore_ascii_case(p));
+
+        if starts_with("prefer ") {
+            prefer.push(clause[7..].trim().to_string());
+        } else if starts_with("no ") {
+            avoid.push(clause[3..].trim().to_string());
+        }
+    }
+
+    (prefer, avoid)
+}
+
+/// Confirms that you're still playing during a ready check.
+///
+/// **Usage:** `[p]ready`
+///
+/// You can also react to the ready check message instead.
+#[command("ready")]
+async fn ready(ctx: &Context, msg: &Message) -> CommandResult {
+    let guild_id = match msg.guild_id {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let check = match sqlx::query!(
+        "SELECT players, confirmed FROM ready_checks WHERE guild_id = $1 AND NOT ended;",
+        guild_id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await?
+    {
+        Some(c) => c,
+        None => {
+            msg.channel_id
+                .say(&ctx.http, "There is no ready check running.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let user_id = msg.author.id.0 as i64;
+    if !check.players.contains(&user_id) {
+        msg.channel_id
+            .say(
+                &ctx.http,
+                "You aren't one of the players in this ready check.",
+            )
+            .await?;
+        return Ok(());
+    }
+
+    if check.confirmed.contains(&user_id) {
+        msg.channel_id
+            .say(&ctx.http, "You have already confirmed.")
+            .await?;
+        return Ok(());
+    }
+
+    sqlx::query!(
+        "UPDATE ready_checks SET confirmed = array_append(confirmed, $2) WHERE guild_id = $1;",
+        guild_id.0 as i64,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    match &msg.react(&ctx.http, '✅').await {
+        Ok(_) => (),
+        Err(_) => {
+            msg.channel_id.say(&ctx.http, "You're ready!").await?;
+        }
+    };
+
     Ok(())
 }
 
@@ -381,6 +549,93 @@ fn initial_checks(settings: &SignSettings) -> Result<(), &'static str> {
     Ok(())
 }
 
+/// Checks the eligibility rules set by the hosts, and the game ban list.
+async fn check_eligibility(
+    ctx: &Context,
+    msg: &Message,
+    guild: &Guild,
+    settings: &SignSettings,
+) -> Result<(), Cow<'static, str>> {
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let ban = match sqlx::query!(
+        "
+        SELECT reason, expires_at FROM game_bans
+        WHERE guild_id = $1 AND user_id = $2 AND (expires_at IS NULL OR expires_at > now());
+        ",
+        guild.id.0 as i64,
+        msg.author.id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await
+    {
+        Ok(b) => b,
+        Err(_) => return Err(Cow::from("I couldn't check the game ban list.")),
+    };
+
+    if let Some(ban) = ban {
+        let mut text = String::from("You are banned from signing up for games.");
+        if let Some(reason) = ban.reason {
+            let _ = write!(text, " Reason: {}", reason);
+        }
+        match ban.expires_at {
+            Some(t) => {
+                let _ = write!(
+                    text,
+                    " The ban ends on {}.",
+                    t.format("%B %-d, %Y at %H:%M UTC")
+                );
+            }
+            None => text.push_str(" The ban is permanent."),
+        }
+
+        return Err(Cow::from(text));
+    }
+
+    let min_account_age = Duration::hours(settings.min_account_age as i64);
+    if Utc::now() - msg.author.created_at() < min_account_age {
+        return Err(Cow::from(format!(
+            "Your Discord account must be at least {} old to sign up.",
+            format_duration(min_account_age)
+        )));
+    }
+
+    let member = match msg.member(ctx).await {
+        Ok(m) => m,
+        Err(_) => return Err(Cow::from("I couldn't fetch details about you.")),
+    };
+
+    let min_member_age = Duration::hours(settings.min_member_age as i64);
+    if settings.min_member_age > 0 {
+        match member.joined_at {
+            Some(t) if Utc::now() - t >= min_member_age => (),
+            _ => {
+                return Err(Cow::from(format!(
+                    "You must be a member of this server for at least {} to sign up.",
+                    format_duration(min_member_age)
+                )))
+            }
+        }
+    }
+
+    if let Some(id) = settings.signup_role_id {
+        let role_id = RoleId(id as u64);
+        if !member.roles.contains(&role_id) {
+            let name = match guild.roles.get(&role_id) {
+                Some(r) => r.name.clone(),
+                None => String::from("required"),
+            };
+            return Err(Cow::from(format!(
+                "You need the `{}` role to sign up.",
+                name
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 async fn get_signups_channel(
     ctx: &Context,
     guild: &Guild,
@@ -413,7 +668,11 @@ async fn get_settings(ctx: &Context, guild: &Guild) -> Option<SignSettings> {
             signups_channel_id,
             player_role_id,
             spec_role_id,
-            repl_role_id
+            repl_role_id,
+            signups_autoclose,
+            min_account_age,
+            min_member_age,
+            signup_role_id
         FROM config WHERE guild_id = $1;",
         guild.id.0 as i64
     )
@@ -546,6 +805,15 @@ async fn remove_role(
             )
             .execute(pool)
             .await?;
+
+            // Preferences only apply to the sign-up they were made with.
+            sqlx::query!(
+                "DELETE FROM signup_prefs WHERE guild_id = $1 AND user_id = $2;",
+                member.guild_id.0 as i64,
+                member.user.id.0 as i64
+            )
+            .execute(pool)
+            .await?;
         }
     }
 
@@ -759,7 +1027,7 @@ async fn all_replacements(ctx: &Context, msg: &Message) -> CommandResult {
 
 /// Displays the vote count.
 ///
-/// **Usage:** `[p]votecount [channel] [--all]`
+/// **Usage:** `[p]votecount [channel] [--all] [--flag]`
 ///
 /// **Alias:** `vc`
 ///
@@ -770,6 +1038,10 @@ async fn all_replacements(ctx: &Context, msg: &Message) -> CommandResult {
 /// The bot only shows votes of *alive* players. If you want to get the votes of all players,
 /// add "--all" at the end of command.
 ///
+/// Add "--flag" to mark players with `*` if they edited or deleted their counted vote,
+/// or a later vote. Hosts are alerted of these changes in the log channel, see
+/// `[p]log event votes`.
+///
 /// **Examples**
 ///
 /// Command: `[p]vc`
@@ -798,7 +1070,7 @@ async fn vote_count(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
 
     let data: VoteData = match sqlx::query_as_unchecked!(
         VoteData,
-        "SELECT player_role_id, players, cycle FROM config WHERE guild_id = $1",
+        "SELECT player_role_id, players, cycle, anonymous FROM config WHERE guild_id = $1",
         guild.id.0 as i64
     )
     .fetch_one(pool)
@@ -814,22 +1086,25 @@ async fn vote_count(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
 
     let cycle = match data.cycle {
         Some(c) => c.0,
-        None => Cycle {
-            number: 0,
-            day: None,
-            night: None,
-            votes: None,
-        },
+        None => Cycle::default(),
     };
 
     // Time for argument parsing
     let all = args.message().contains("--all");
+    let flag = args.message().contains("--flag");
 
     // Check if user passed a channel.
     let channel = match get_channel(
         ctx,
         guild.id,
-        Some(&args.message().replace("--all", "").to_string()),
+        Some(
+            &args
+                .message()
+                .replace("--all", "")
+                .replace("--flag", "")
+                .trim()
+                .to_string(),
+        ),
     )
     .await
     {
@@ -894,20 +1169,80 @@ async fn vote_count(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
             .collect()
     };
 
+    // In anonymous games, votes are posted by webhooks under the aliases of players.
+    let aliases: HashMap<UserId, String> = if data.anonymous {
+        sqlx::query!(
+            "SELECT user_id, alias FROM aliases WHERE guild_id = $1;",
+            guild.id.0 as i64
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|a| (UserId(a.user_id as u64), a.alias))
+        .collect()
+    } else {
+        HashMap::new()
+    };
+    let alias_owners: HashMap<&str, &User> = players
+        .iter()
+        .filter_map(|u| aliases.get(&u.id).map(|a| (a.as_str(), u)))
+        .collect();
+    // Only votes relayed by the bot count, not other webhooks using an alias.
+    let relay_webhook_id = if data.anonymous {
+        get_webhook(ctx, &channel).await.ok().map(|w| w.id)
+    } else {
+        None
+    };
+
     let mut user_votes = HashMap::new();
+    let mut vote_messages: HashMap<UserId, MessageId> = HashMap::new();
     let mut messages = channel.id.messages_iter(&ctx).boxed();
     while let Some(message) = messages.next().await {
         if let Ok(message) = message {
-            if !players.contains(&message.author) || user_votes.contains_key(&message.author) {
+            let voter = if message.webhook_id.is_some() {
+                if message.webhook_id != relay_webhook_id {
+                    continue;
+                }
+                match alias_owners.get(message.author.name.as_str()) {
+                    Some(u) => (*u).clone(),
+                    None => continue,
+                }
+            } else {
+                message.author.clone()
+            };
+
+            if !players.contains(&voter) || user_votes.contains_key(&voter) {
                 continue;
             }
             let vote_res = get_vote_from_message(clean_user_mentions(&message));
             if let Some(vote) = vote_res {
-                user_votes.insert(message.author, Some(vote));
+                vote_messages.insert(voter.id, message.id);
+                user_votes.insert(voter, Some(canonical_vote(&aliases, vote)));
             }
         }
     }
 
+    // A change matters if it was made to the counted vote, or to a later
+    // message which doesn't count as a vote anymore.
+    let changed: HashSet<UserId> = if flag {
+        sqlx::query!(
+            "SELECT user_id, message_id FROM vote_changes WHERE channel_id = $1;",
+            channel.id.0 as i64
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|c| (UserId(c.user_id as u64), MessageId(c.message_id as u64)))
+        .filter(|(user_id, message_id)| match vote_messages.get(user_id) {
+            Some(counted) => message_id >= counted,
+            None => true,
+        })
+        .map(|(user_id, _)| user_id)
+        .collect()
+    } else {
+        HashSet::new()
+    };
+
     // Adds non-voters to `user_votes`.
     get_non_voters(players, &mut user_votes);
 
@@ -940,7 +1275,17 @@ async fn vote_count(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     for (idx, (vote, voters)) in votes.iter().enumerate() {
         let voters: Vec<_> = voters
             .iter()
-            .map(|m| format!("{}#{}", m.name, m.discriminator))
+            .map(|m| {
+                let name = match aliases.get(&m.id) {
+                    Some(a) => a.clone(),
+                    None => format!("{}#{}", m.name, m.discriminator),
+                };
+                if changed.contains(&m.id) {
+                    format!("{}\\*", name)
+                } else {
+                    name
+                }
+            })
             .collect();
 
         match vote {
@@ -970,11 +1315,14 @@ async fn vote_count(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
         };
     }
 
-    let desc = format!(
+    let mut desc = format!(
         "__Counting from {} channel.__\n\n{}",
         channel.mention(),
         votes_str.trim()
     );
+    if votes.values().flatten().any(|u| changed.contains(&u.id)) {
+        desc.push_str("\n\n\\* Edited or deleted the vote that counts.");
+    }
 
     let rep = msg
         .channel_id
@@ -1001,7 +1349,7 @@ async fn vote_count(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     Ok(())
 }
 
-fn get_vote_from_message(content: String) -> Option<Vote> {
+pub(crate) fn get_vote_from_message(content: String) -> Option<Vote> {
     let vote_re: Lazy<Regex> =
         Lazy::new(|| Regex::new(r"^[\*_~|]*[Vv][Tt][Ll][\*_~|]*[\s\*_~|]+([^\*_~|]+)").unwrap());
     let un_vote_re: Lazy<Regex> = Lazy::new(|| {
@@ -1025,6 +1373,26 @@ fn get_vote_from_message(content: String) -> Option<Vote> {
     }
 }
 
+/// Writes aliases in a vote the same way, no matter how they were typed.
+pub(crate) fn canonical_vote(aliases: &HashMap<UserId, String>, vote: Vote) -> Vote {
+    match vote {
+        Vote::Vtl(s) => Vote::Vtl(canonical_alias(aliases, s)),
+        Vote::UnVtl(s) => Vote::UnVtl(canonical_alias(aliases, s)),
+        v => v,
+    }
+}
+
+/// Returns the alias as it was set if `name` matches one, ignoring case.
+fn canonical_alias(aliases: &HashMap<UserId, String>, name: String) -> String {
+    match aliases
+        .values()
+        .find(|a| a.trim().eq_ignore_ascii_case(name.trim()))
+    {
+        Some(a) => a.clone(),
+        None => name,
+    }
+}
+
 fn get_non_voters(players: HashSet<User>, votes: &mut HashMap<User, Option<Vote>>) {
     for player in players {
         votes.entry(player).or_insert(None);
@@ -1147,7 +1515,7 @@ async fn time_since(ctx: &Context, msg: &Message) -> CommandResult {
     Ok(())
 }
 
-fn is_day(day_channel: &GuildChannel, player_role: &Role) -> bool {
+pub(crate) fn is_day(day_channel: &GuildChannel, player_role: &Role) -> bool {
     for overwrites in &day_channel.permission_overwrites {
         match overwrites.kind {
             PermissionOverwriteType::Role(r) => {
@@ -1224,18 +1592,7 @@ async fn night_action(ctx: &Context, msg: &Message, args: Args) -> CommandResult
 
     match get_channel_from_id(ctx, guild.id, Some(msg.channel_id.0 as i64)).await {
         Ok(c) => {
-            let private = c.permission_overwrites.iter().any(|p| match p.kind {
-                PermissionOverwriteType::Member(m) => {
-                    if m == msg.author.id {
-                        p.allow.send_messages()
-                    } else {
-                        false
-                    }
-                }
-                _ => false,
-            });
-
-            if !private {
+            if !is_private_chat(&c, msg.author.id) {
                 msg.channel_id.say(
                     &ctx.http,
                     "This doesn't look like your private channel. This command can only be used in your private channel."
@@ -1326,6 +1683,320 @@ async fn night_action(ctx: &Context, msg: &Message, args: Args) -> CommandResult
     Ok(())
 }
 
+/// Checks if `user_id` is allowed to talk in the channel by a member overwrite.
+pub(crate) fn is_private_chat(channel: &GuildChannel, user_id: UserId) -> bool {
+    channel.permission_overwrites.iter().any(|p| match p.kind {
+        PermissionOverwriteType::Member(m) => m == user_id && p.allow.send_messages(),
+        _ => false,
+    })
+}
+
+/// Finds the private channel of a player.
+///
+/// It is the chat stored by `playerchats`. For chats made another way, it is
+/// the channel where the player is the only member, other than me, who is
+/// allowed to talk by a member overwrite.
+pub(crate) async fn find_private_chat(
+    pool: &PgPool,
+    guild: &Guild,
+    user_id: UserId,
+    bot_id: UserId,
+) -> Option<GuildChannel> {
+    let stored = sqlx::query!(
+        "
+        SELECT channel_id FROM player_chats
+        WHERE guild_id = $1 AND user_id = $2 AND NOT archived;
+        ",
+        guild.id.0 as i64,
+        user_id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten();
+
+    if let Some(c) = stored.and_then(|r| guild.channels.get(&ChannelId(r.channel_id as u64))) {
+        return Some(c.clone());
+    }
+
+    guild
+        .channels
+        .values()
+        .find(|c| {
+            let members: Vec<UserId> = c
+                .permission_overwrites
+                .iter()
+                .filter_map(|p| match p.kind {
+                    PermissionOverwriteType::Member(m)
+                        if m != bot_id && p.allow.send_messages() =>
+                    {
+                        Some(m)
+                    }
+                    _ => None,
+                })
+                .collect();
+
+            members == [user_id]
+        })
+        .cloned()
+}
+
+struct WhisperSettings {
+    whispers_on: bool,
+    whispers_public: bool,
+    whisper_limit: i32,
+    player_role_id: Option<i64>,
+    dead_role_id: Option<i64>,
+    cycle: Option<Json<Cycle>>,
+}
+
+/// Whispers a message to another player.
+///
+/// **Usage:** `[p]whisper <player> <message>`
+///
+/// **Alias:** `w`
+///
+/// The command can only be used in your private channel. I send the message
+/// to the private channel of the player, and the hosts get a copy.
+///
+/// Hosts decide if whispers are allowed, if everyone is told who whispered to
+/// whom in the day channel, and how many whispers you can send each day.
+/// Dead players can't whisper.
+#[command("whisper")]
+#[aliases("w")]
+#[min_args(2)]
+async fn whisper(ctx: &Context, msg: &Message, mut args: Args) -> CommandResult {
+    let guild = match msg.guild(ctx).await {
+        Some(i) => i,
+        None => return Err(CommandError::from("Couldn't fetch details of this server.")),
+    };
+
+    match get_channel_from_id(ctx, guild.id, Some(msg.channel_id.0 as i64)).await {
+        Ok(c) => {
+            if !is_private_chat(&c, msg.author.id) {
+                msg.channel_id.say(
+                    &ctx.http,
+                    "This doesn't look like your private channel. This command can only be used in your private channel."
+                ).await?;
+                return Ok(());
+            }
+        }
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, "Unable to get details of this channel.")
+                .await?;
+            return Ok(());
+        }
+    }
+
+    let input = args.single_quoted::<String>()?;
+    let text = args.rest();
+
+    let target = match get_member(ctx, guild.id, Some(&input)).await {
+        Ok(m) => m,
+        Err(_) => {
+            msg.channel_id
+                .say(&ctx.http, format!("No member found from `{}`.", input))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if target.user.id == msg.author.id {
+        msg.channel_id
+            .say(&ctx.http, "You can't whisper to yourself.")
+            .await?;
+        return Ok(());
+    }
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let settings = match sqlx::query_as_unchecked!(
+        WhisperSettings,
+        "
+        SELECT
+            whispers_on,
+            whispers_public,
+            whisper_limit,
+            player_role_id,
+            dead_role_id,
+            cycle
+        FROM config WHERE guild_id = $1;
+        ",
+        guild.id.0 as i64
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(s) => s,
+        Err(_) => {
+            return Err(CommandError::from(
+                "Couldn't fetch details from the database.",
+            ))
+        }
+    };
+
+    if !settings.whispers_on {
+        msg.channel_id
+            .say(&ctx.http, "Whispers are not allowed in this game.")
+            .await?;
+        return Ok(());
+    }
+
+    let cycle = match settings.cycle {
+        Some(c) if c.number > 0 => c.0,
+        _ => {
+            msg.channel_id
+                .say(&ctx.http, "You can't whisper before the game starts.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let player_role_id = match settings.player_role_id {
+        Some(i) => RoleId(i as u64),
+        None => {
+            msg.channel_id
+                .say(&ctx.http, "Player role has not been set up.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let sender = msg.member(ctx).await?;
+    if !sender.roles.contains(&player_role_id) {
+        let is_dead = matches!(
+            settings.dead_role_id,
+            Some(i) if sender.roles.contains(&RoleId(i as u64))
+        );
+        let reply = if is_dead {
+            "Dead players can't whisper."
+        } else {
+            "Only players can whisper."
+        };
+        msg.channel_id.say(&ctx.http, reply).await?;
+        return Ok(());
+    }
+
+    if !target.roles.contains(&player_role_id) {
+        msg.channel_id
+            .say(&ctx.http, "You can only whisper to players who are alive.")
+            .await?;
+        return Ok(());
+    }
+
+    if settings.whisper_limit > 0 {
+        let sent = sqlx::query!(
+            "
+            SELECT count(*) AS \"count!\" FROM whispers
+            WHERE guild_id = $1 AND sender_id = $2 AND cycle = $3;
+            ",
+            guild.id.0 as i64,
+            msg.author.id.0 as i64,
+            cycle.number
+        )
+        .fetch_one(pool)
+        .await?;
+
+        if sent.count >= settings.whisper_limit as i64 {
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    format!(
+                        "You have used all {} of your whispers for today.",
+                        settings.whisper_limit
+                    ),
+                )
+                .await?;
+            return Ok(());
+        }
+    }
+
+    let bot_id = ctx.cache.current_user_id().await;
+    let target_channel = match find_private_chat(pool, &guild, target.user.id, bot_id).await {
+        Some(c) => c,
+        None => {
+            msg.channel_id
+                .say(
+                    &ctx.http,
+                    format!(
+                        "I couldn't find the private channel of {}.",
+                        target.display_name()
+                    ),
+                )
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let whisper_log = match get_whisper_log(ctx, &guild, pool).await {
+        Ok(c) => c,
+        Err(e) => return Err(CommandError::from(e)),
+    };
+
+    let options = ContentSafeOptions::new().display_as_member_from(guild.id);
+    let text = content_safe(&ctx.cache, text, &options).await;
+
+    target_channel
+        .say(
+            &ctx.http,
+            format!("**Whisper from {}**\n{}", sender.display_name(), text),
+        )
+        .await?;
+
+    whisper_log
+        .say(
+            &ctx.http,
+            format!(
+                "**{} whispered to {}** (Day {})\n{}",
+                sender.display_name(),
+                target.display_name(),
+                cycle.number,
+                text
+            ),
+        )
+        .await?;
+
+    sqlx::query!(
+        "
+        INSERT INTO whispers (guild_id, cycle, sender_id, target_id, content)
+        VALUES ($1, $2, $3, $4, $5);
+        ",
+        guild.id.0 as i64,
+        cycle.number,
+        msg.author.id.0 as i64,
+        target.user.id.0 as i64,
+        text
+    )
+    .execute(pool)
+    .await?;
+
+    if settings.whispers_public {
+        if let Some(day) = cycle.day {
+            ChannelId(day as u64)
+                .say(
+                    &ctx.http,
+                    format!(
+                        "**{}** whispered to **{}**.",
+                        sender.display_name(),
+                        target.display_name()
+                    ),
+                )
+                .await?;
+        }
+    }
+
+    match &msg.react(&ctx.http, '✅').await {
+        Ok(_) => (),
+        Err(_) => {
+            msg.channel_id.say(&ctx.http, "Whisper sent!").await?;
+        }
+    };
+
+    Ok(())
+}
+
 /// Parses supplied CommonMark Markdown text and attaches formatted JPEG and PDF.
 ///
 /// **Usage:** `[p]format <message>`
@@ -1639,12 +2310,7 @@ async fn vote_history(ctx: &Context, msg: &Message, mut args: Args) -> CommandRe
             if let Some(c) = cf.cycle {
                 c.0
             } else {
-                Cycle {
-                    number: 0,
-                    day: None,
-                    night: None,
-                    votes: None,
-                }
+                Cycle::default()
             }
         }
         Err(_) => {
@@ -1879,11 +2545,13 @@ async fn notify(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
     sign_in,
     sign_out,
     sign_repl,
+    ready,
     all_players,
     all_replacements,
     vote_count,
     time_since,
     night_action,
+    whisper,
     format_text,
     tos_wiki,
     top_cmd,
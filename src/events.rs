@@ -1,24 +1,46 @@
 // This module contains functions to handle message events fired by serenity.
 //
-// We only log message edits and deletions.
+// Messages in logged channels can also be archived, see `/src/archive.rs`.
+// Member and channel events are logged in `/src/server_events.rs`.
 
 use crate::{
-    utils::{converters::get_channel_from_id, message::get_jump_url},
-    ConnectionPool,
+    archive::{
+        get_archived_message, get_archived_messages, get_stored_files, mark_archived_deleted,
+        update_archived_message, ArchivedMessage, StoredFile,
+    },
+    commands::setup::Cycle,
+    ghost_pings::{ghost_ping_handler, PingedMessage, Pings},
+    relay::{AnonSettings, ChannelLink},
+    utils::{
+        converters::get_channel_from_id,
+        diff::{inline_diff, unified_diff, whitespace_only},
+        message::get_jump_url,
+    },
+    vote_changes::{vote_change_handler, VoteMessage},
+    ConnectionPool, StorageContainer,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serenity::{
     http::AttachmentType,
     model::{
         misc::Mentionable,
-        prelude::{ChannelId, GuildChannel, Message, MessageId, MessageUpdateEvent, RoleId},
+        prelude::{
+            ChannelId, GuildChannel, GuildId, Message, MessageId, MessageUpdateEvent, RoleId,
+            UserId,
+        },
     },
     prelude::Context,
 };
 use serenity_utils::{formatting::text_to_file, prelude::EmbedBuilder};
+use sqlx::{types::Json, PgPool};
+use std::fmt::Write;
 
 use tracing::{error, instrument};
 
+/// Largest total size of files Discord accepts in one message, in bytes.
+pub(crate) const UPLOAD_LIMIT: usize = 8 * 1024 * 1024;
+
+// New columns must be added to `get_message_settings` too.
 pub(crate) struct LogSettings {
     // `guild_id` exists here so we can use `*` in sql queries.
     // It is not intended to be read.
@@ -27,6 +49,175 @@ pub(crate) struct LogSettings {
     pub(crate) log_channel_id: Option<i64>,
     pub(crate) blacklist_channel_ids: Option<Vec<i64>>,
     pub(crate) whitelist_channel_ids: Option<Vec<i64>>,
+    pub(crate) archive_on: bool,
+    pub(crate) archive_retention: i32,
+    pub(crate) attachments_on: bool,
+    pub(crate) attachment_max_size: i32,
+    pub(crate) attachment_retention: i32,
+    pub(crate) disabled_events: Vec<String>,
+    pub(crate) ghost_pings_in_channel: bool,
+}
+
+/// Settings every new message is handled with.
+pub(crate) struct MessageSettings {
+    pub(crate) log: Option<LogSettings>,
+    pub(crate) anon: AnonSettings,
+    pub(crate) link: Option<ChannelLink>,
+}
+
+/// Gets the settings for a new message in one query, since every message in
+/// every guild needs them.
+pub(crate) async fn get_message_settings(
+    pool: &PgPool,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+) -> Option<MessageSettings> {
+    let res = sqlx::query!(
+        r#"
+        SELECT
+            c.anonymous, c.anon_prefix, c.player_role_id,
+            c.cycle AS "cycle: Json<Cycle>", c.anonymous_links,
+            l.guild_id AS "log_guild_id?", l.log_channel_id AS "log_channel_id?",
+            l.blacklist_channel_ids AS "blacklist_channel_ids?",
+            l.whitelist_channel_ids AS "whitelist_channel_ids?",
+            l.archive_on AS "archive_on?", l.archive_retention AS "archive_retention?",
+            l.attachments_on AS "attachments_on?",
+            l.attachment_max_size AS "attachment_max_size?",
+            l.attachment_retention AS "attachment_retention?",
+            l.disabled_events AS "disabled_events?",
+            l.ghost_pings_in_channel AS "ghost_pings_in_channel?",
+            k.first_channel_id AS "first_channel_id?",
+            k.second_channel_id AS "second_channel_id?",
+            k.first_name AS "first_name?"
+        FROM config AS c
+        LEFT JOIN logging AS l ON l.guild_id = c.guild_id
+        LEFT JOIN channel_links AS k ON k.guild_id = c.guild_id
+            AND (k.first_channel_id = $2 OR k.second_channel_id = $2)
+        WHERE c.guild_id = $1 LIMIT 1;
+        "#,
+        guild_id.0 as i64,
+        channel_id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await;
+
+    let r = match res {
+        Ok(Some(r)) => r,
+        Ok(None) => return None,
+        Err(why) => {
+            error!("Unable to fetch message settings: {}", why);
+            return None;
+        }
+    };
+
+    let log = match r.log_guild_id {
+        Some(guild_id) => Some(LogSettings {
+            guild_id,
+            log_channel_id: r.log_channel_id,
+            blacklist_channel_ids: r.blacklist_channel_ids,
+            whitelist_channel_ids: r.whitelist_channel_ids,
+            archive_on: r.archive_on.unwrap_or_default(),
+            archive_retention: r.archive_retention.unwrap_or_default(),
+            attachments_on: r.attachments_on.unwrap_or_default(),
+            attachment_max_size: r.attachment_max_size.unwrap_or_default(),
+            attachment_retention: r.attachment_retention.unwrap_or_default(),
+            disabled_events: r.disabled_events.unwrap_or_default(),
+            ghost_pings_in_channel: r.ghost_pings_in_channel.unwrap_or_default(),
+        }),
+        None => None,
+    };
+
+    let link = match (r.first_channel_id, r.second_channel_id) {
+        (Some(first_channel_id), Some(second_channel_id)) => Some(ChannelLink {
+            first_channel_id,
+            second_channel_id,
+            first_name: r.first_name,
+            anonymous_links: r.anonymous_links,
+        }),
+        _ => None,
+    };
+
+    Some(MessageSettings {
+        log,
+        anon: AnonSettings {
+            anonymous: r.anonymous,
+            anon_prefix: r.anon_prefix,
+            player_role_id: r.player_role_id,
+            cycle: r.cycle,
+        },
+        link,
+    })
+}
+
+/// Kinds of events that can be logged.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum LogEvent {
+    Edits,
+    Deletes,
+    Roles,
+    Nicknames,
+    Joins,
+    Overwrites,
+    GhostPings,
+    Votes,
+    Audit,
+}
+
+impl LogEvent {
+    pub(crate) const ALL: [LogEvent; 9] = [
+        Self::Edits,
+        Self::Deletes,
+        Self::Roles,
+        Self::Nicknames,
+        Self::Joins,
+        Self::Overwrites,
+        Self::GhostPings,
+        Self::Votes,
+        Self::Audit,
+    ];
+
+    /// Name used in commands and in the database.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Edits => "edits",
+            Self::Deletes => "deletes",
+            Self::Roles => "roles",
+            Self::Nicknames => "nicknames",
+            Self::Joins => "joins",
+            Self::Overwrites => "overwrites",
+            Self::GhostPings => "ghostpings",
+            Self::Votes => "votes",
+            Self::Audit => "audit",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|e| e.name().eq_ignore_ascii_case(name))
+            .copied()
+    }
+
+    pub(crate) fn description(self) -> &'static str {
+        match self {
+            Self::Edits => "Message Edits",
+            Self::Deletes => "Message Deletes",
+            Self::Roles => "Role Changes",
+            Self::Nicknames => "Nickname Changes",
+            Self::Joins => "Joins and Leaves",
+            Self::Overwrites => "Permission Changes",
+            Self::GhostPings => "Ghost Pings",
+            Self::Votes => "Vote Changes",
+            Self::Audit => "Host Commands",
+        }
+    }
+}
+
+impl LogSettings {
+    /// Checks if `event` is logged.
+    pub(crate) fn logs(&self, event: LogEvent) -> bool {
+        !self.disabled_events.iter().any(|e| e == event.name())
+    }
 }
 
 #[instrument(skip(ctx))]
@@ -36,10 +227,23 @@ pub(crate) async fn message_update_handler(
     new: Option<Message>,
     event: MessageUpdateEvent,
 ) {
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
     // We can't compare messages if we don't get the old one.
-    let old_content = match old_if_available {
-        Some(m) => m.content,
-        None => match event.content {
+    // The archive has it even if the cache doesn't.
+    let archived = match old_if_available {
+        Some(_) => None,
+        None => get_archived_message(pool, event.id).await,
+    };
+    let old_pings = match &old_if_available {
+        Some(m) => Pings::from_message(m),
+        None => Pings::default(),
+    };
+    let old_content = match (old_if_available, &archived) {
+        (Some(m), _) => m.content,
+        (None, Some(a)) => a.content.clone(),
+        (None, None) => match event.content {
             Some(c) => c,
             None => {
                 error!("I couldn't get old message content.");
@@ -49,6 +253,10 @@ pub(crate) async fn message_update_handler(
     };
     let new = match new {
         Some(m) => m,
+        None if archived.is_some() => match event.channel_id.message(&ctx.http, event.id).await {
+            Ok(m) => m,
+            Err(_) => return,
+        },
         None => return,
     };
 
@@ -56,13 +264,36 @@ pub(crate) async fn message_update_handler(
         return;
     }
 
+    update_archived_message(pool, new.id, &new.content).await;
+
     let guild = match new.guild(&ctx).await {
         Some(i) => i,
         None => return,
     };
 
-    let data_read = ctx.data.read().await;
-    let pool = data_read.get::<ConnectionPool>().unwrap();
+    if !new.author.bot {
+        // Mentions in the old content count too, in case the old message wasn't cached.
+        let pings = Pings::from_content(&old_content)
+            .merge(old_pings)
+            .removed(&Pings::from_message(&new));
+        let message = PingedMessage {
+            guild_id: guild.id,
+            channel_id: new.channel_id,
+            author_id: new.author.id,
+            author_tag: &new.author.tag(),
+            content: &old_content,
+        };
+        ghost_ping_handler(&ctx, pool, message, pings, true).await;
+
+        let message = VoteMessage {
+            guild_id: guild.id,
+            channel_id: new.channel_id,
+            message_id: new.id,
+            author_id: new.author.id,
+            author_tag: &new.author.tag(),
+        };
+        vote_change_handler(&ctx, pool, message, &old_content, Some(&new.content)).await;
+    }
 
     let settings: LogSettings = match sqlx::query_as!(
         LogSettings,
@@ -84,11 +315,12 @@ pub(crate) async fn message_update_handler(
         None => return,
     };
 
-    if !is_allowed_channel(&ctx, channel, &settings).await {
+    if !settings.logs(LogEvent::Edits) || !is_allowed_channel(&ctx, channel, &settings).await {
         return;
     }
 
-    let log_channel = match get_channel_from_id(&ctx, guild.id, settings.log_channel_id).await {
+    let log_channel = match get_log_channel(&ctx, pool, guild.id, &settings, LogEvent::Edits).await
+    {
         Ok(c) => c,
         Err(_) => return,
     };
@@ -103,9 +335,18 @@ pub(crate) async fn message_update_handler(
             get_jump_url(&new)
         ));
 
-    let old_file = get_added_fields_and_file(&old_content, &mut embed, "Before");
+    // Small edits are shown inline. Otherwise, we'll show a unified diff instead.
+    // Changes to whitespace alone don't show up inline, so they get a diff too.
+    let changes = inline_diff(&old_content, &new.content);
+    let diff;
+    let file = if changes.len() <= 1024 && !whitespace_only(&old_content, &new.content) {
+        embed.add_field(("Changes", &changes, false));
 
-    let new_file = get_added_fields_and_file(&new.content, &mut embed, "After");
+        None
+    } else {
+        diff = unified_diff(&old_content, &new.content, "before", "after");
+        get_added_fields_and_file(&diff, &mut embed, "Diff")
+    };
 
     embed
         .add_field(("Channel", channel.mention(), false))
@@ -119,7 +360,7 @@ pub(crate) async fn message_update_handler(
             .set_icon_url(new.author.face())
         });
 
-    let files = vec![old_file, new_file].into_iter().flatten();
+    let files = file.into_iter();
 
     let msg = &log_channel
         .send_message(&ctx.http, |m| m.set_embed(embed.to_create_embed()))
@@ -138,10 +379,127 @@ pub(crate) async fn message_delete_handler(
     channel_id: ChannelId,
     deleted_message_id: MessageId,
 ) {
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
     match ctx.cache.message(channel_id, deleted_message_id).await {
-        Some(m) => cached_message_handler(&ctx, &m).await,
-        None => uncached_message_handler(&ctx, channel_id, deleted_message_id).await,
+        Some(m) => {
+            cached_message_handler(&ctx, &m).await;
+
+            if let (Some(guild_id), false) = (m.guild_id, m.author.bot) {
+                let message = PingedMessage {
+                    guild_id,
+                    channel_id,
+                    author_id: m.author.id,
+                    author_tag: &m.author.tag(),
+                    content: &m.content,
+                };
+                ghost_ping_handler(&ctx, pool, message, Pings::from_message(&m), false).await;
+
+                let message = VoteMessage {
+                    guild_id,
+                    channel_id,
+                    message_id: m.id,
+                    author_id: m.author.id,
+                    author_tag: &m.author.tag(),
+                };
+                vote_change_handler(&ctx, pool, message, &m.content, None).await;
+            }
+        }
+        None => match get_archived_message(pool, deleted_message_id).await {
+            Some(m) => {
+                archived_message_handler(&ctx, channel_id, &m).await;
+
+                if let Some(channel) = ctx.cache.guild_channel(channel_id).await {
+                    let message = PingedMessage {
+                        guild_id: channel.guild_id,
+                        channel_id,
+                        author_id: UserId(m.author_id as u64),
+                        author_tag: &m.author_tag,
+                        content: &m.content,
+                    };
+                    let pings = Pings::from_content(&m.content);
+                    ghost_ping_handler(&ctx, pool, message, pings, false).await;
+
+                    let message = VoteMessage {
+                        guild_id: channel.guild_id,
+                        channel_id,
+                        message_id: deleted_message_id,
+                        author_id: UserId(m.author_id as u64),
+                        author_tag: &m.author_tag,
+                    };
+                    vote_change_handler(&ctx, pool, message, &m.content, None).await;
+                }
+            }
+            None => uncached_message_handler(&ctx, channel_id, deleted_message_id).await,
+        },
     };
+
+    send_stored_files(&ctx, pool, deleted_message_id).await;
+
+    mark_archived_deleted(pool, &[deleted_message_id]).await;
+}
+
+/// Uploads the stored attachments of a deleted message to the log channel.
+#[instrument(skip(ctx, pool))]
+async fn send_stored_files(ctx: &Context, pool: &PgPool, message_id: MessageId) {
+    let data_read = ctx.data.read().await;
+    let storage = data_read.get::<StorageContainer>().unwrap();
+
+    let (guild_id, files) = match get_stored_files(pool, storage, message_id).await {
+        Some((g, f)) if !f.is_empty() => (g, f),
+        _ => return,
+    };
+
+    let settings = match sqlx::query_as!(
+        LogSettings,
+        "SELECT * FROM logging WHERE guild_id = $1;",
+        guild_id.0 as i64
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+
+    if !settings.logs(LogEvent::Deletes) {
+        return;
+    }
+
+    let log_channel = match get_log_channel(ctx, pool, guild_id, &settings, LogEvent::Deletes).await
+    {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+
+    // Discord limits the total size of files in one message, so we may need more messages.
+    let mut batches: Vec<Vec<&StoredFile>> = Vec::new();
+    let mut batch_size = 0;
+    for file in &files {
+        if batches.is_empty() || batch_size + file.data.len() > UPLOAD_LIMIT {
+            batches.push(Vec::new());
+            batch_size = 0;
+        }
+        batch_size += file.data.len();
+        batches.last_mut().unwrap().push(file);
+    }
+
+    for batch in batches {
+        let attachments: Vec<AttachmentType> = batch.iter().map(|f| f.to_attachment()).collect();
+        let res = log_channel
+            .send_files(&ctx.http, attachments, |m| {
+                m.content(format!(
+                    "Attachments of deleted message `{}`:",
+                    message_id.0
+                ))
+            })
+            .await;
+
+        if let Err(why) = res {
+            error!("Failed to upload stored attachments: {}", why);
+        }
+    }
 }
 
 #[instrument(skip(ctx))]
@@ -175,6 +533,8 @@ pub(crate) async fn message_delete_bulk_handler(
     let data_read = ctx.data.read().await;
     let pool = data_read.get::<ConnectionPool>().unwrap();
 
+    mark_archived_deleted(pool, &message_ids).await;
+
     let settings: LogSettings = match sqlx::query_as!(
         LogSettings,
         "SELECT * FROM logging WHERE guild_id = $1;",
@@ -190,24 +550,74 @@ pub(crate) async fn message_delete_bulk_handler(
         }
     };
 
-    if !is_allowed_channel(&ctx, &channel, &settings).await {
+    if !settings.logs(LogEvent::Deletes) || !is_allowed_channel(&ctx, &channel, &settings).await {
         return;
     }
 
-    let log_channel = match get_channel_from_id(&ctx, guild_id, settings.log_channel_id).await {
-        Ok(c) => c,
-        Err(_) => return,
+    let log_channel =
+        match get_log_channel(&ctx, pool, guild_id, &settings, LogEvent::Deletes).await {
+            Ok(c) => c,
+            Err(_) => return,
+        };
+
+    // All checks passed. We'll put every message we still know in a file.
+    let mut known = Vec::new();
+    let mut uncached = Vec::new();
+    for id in &message_ids {
+        match ctx.cache.message(channel_id, id).await {
+            Some(m) if !m.author.bot => known.push(KnownMessage {
+                author_id: m.author.id.0 as i64,
+                author_tag: m.author.tag(),
+                created_at: m.timestamp,
+                content: m.content,
+                attachments: m
+                    .attachments
+                    .into_iter()
+                    .map(|a| format!("{} ({})", a.filename, a.url))
+                    .collect(),
+            }),
+            Some(_) => (),
+            None => uncached.push(*id),
+        }
+    }
+    for m in get_archived_messages(pool, &uncached).await {
+        known.push(KnownMessage {
+            author_id: m.author_id,
+            author_tag: m.author_tag,
+            created_at: m.created_at,
+            content: m.content,
+            attachments: m
+                .attachments
+                .0
+                .into_iter()
+                .map(|a| format!("{} ({})", a.filename, a.url))
+                .collect(),
+        });
+    }
+
+    let file = if known.is_empty() {
+        None
+    } else {
+        let text = bulk_delete_text(&channel.name, message_ids.len(), known.len(), known);
+        Some(text_to_file(
+            &text,
+            Some(String::from("deleted-messages.txt")),
+            false,
+        ))
+    };
+    let description = match &file {
+        Some(_) => format!(
+            "{} messages were deleted at once. The ones I know are attached.",
+            message_ids.len()
+        ),
+        None => format!("{} messages were deleted at once.", message_ids.len()),
     };
 
-    // All checks passed. Since we only store 10 messages in the cache, we'll just log the count.
     let msg = log_channel
         .send_message(&ctx.http, |m| {
             m.embed(|e| {
                 e.colour(0xFF0000);
-                e.description(format!(
-                    "{} messages were deleted at once.",
-                    message_ids.len()
-                ));
+                e.description(description);
                 e.timestamp(Utc::now().to_rfc3339());
                 e.field("Channel", channel.mention(), true);
                 e.author(|a| {
@@ -218,6 +628,9 @@ pub(crate) async fn message_delete_bulk_handler(
 
                 e
             });
+            if let Some(f) = file {
+                m.add_file(f);
+            }
 
             m
         })
@@ -264,11 +677,12 @@ async fn cached_message_handler(ctx: &Context, message: &Message) {
         None => return,
     };
 
-    if !is_allowed_channel(&ctx, channel, &settings).await {
+    if !settings.logs(LogEvent::Deletes) || !is_allowed_channel(&ctx, channel, &settings).await {
         return;
     }
 
-    let log_channel = match get_channel_from_id(&ctx, guild.id, settings.log_channel_id).await {
+    let log_channel = match get_log_channel(ctx, pool, guild.id, &settings, LogEvent::Deletes).await
+    {
         Ok(c) => c,
         Err(_) => return,
     };
@@ -340,6 +754,79 @@ async fn cached_message_handler(ctx: &Context, message: &Message) {
     }
 }
 
+/// Logs a deleted message which isn't in the cache, but is in the archive.
+#[instrument(skip(ctx))]
+async fn archived_message_handler(ctx: &Context, channel_id: ChannelId, message: &ArchivedMessage) {
+    let channel = match ctx.cache.guild_channel(channel_id).await {
+        Some(c) => c,
+        None => return,
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let settings: LogSettings = match sqlx::query_as!(
+        LogSettings,
+        "SELECT * FROM logging WHERE guild_id = $1;",
+        channel.guild_id.0 as i64
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(r) => r,
+        Err(_) => {
+            error!("Unable to get logging channel from database.");
+            return;
+        }
+    };
+
+    if !settings.logs(LogEvent::Deletes) || !is_allowed_channel(ctx, &channel, &settings).await {
+        return;
+    }
+
+    let log_channel =
+        match get_log_channel(ctx, pool, channel.guild_id, &settings, LogEvent::Deletes).await {
+            Ok(c) => c,
+            Err(_) => return,
+        };
+
+    let mut embed = EmbedBuilder::new();
+    embed
+        .set_description(&message.content)
+        .set_colour(0xFF0000)
+        .set_timestamp(Utc::now().to_rfc3339())
+        .add_field(("Channel", channel.mention(), true))
+        .add_field(("Sent At", message.created_at.format("%F %T UTC"), true));
+
+    if !message.attachments.is_empty() {
+        let file_names = message
+            .attachments
+            .iter()
+            .map(|a| a.filename.clone())
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        embed.add_field(("Attachments", file_names, true));
+    }
+    embed
+        .set_footer_with(|f| f.set_text(format!("Author ID: {}", message.author_id)))
+        .set_author_with(|a| {
+            a.set_name(format!(
+                "{} ({}) - Deleted Message",
+                message.author_tag, message.author_id
+            ))
+            .set_icon_url(&message.author_avatar)
+        });
+
+    let msg = &log_channel
+        .send_message(&ctx.http, |m| m.set_embed(embed.to_create_embed()))
+        .await;
+
+    if let Err(why) = msg {
+        error!("Failed to log archived message delete: {}", why);
+    }
+}
+
 #[instrument(skip(ctx))]
 async fn uncached_message_handler(ctx: &Context, channel_id: ChannelId, message_id: MessageId) {
     let channel = match channel_id.to_channel(&ctx.http).await {
@@ -377,11 +864,12 @@ async fn uncached_message_handler(ctx: &Context, channel_id: ChannelId, message_
         }
     };
 
-    if !is_allowed_channel(&ctx, &channel, &settings).await {
+    if !settings.logs(LogEvent::Deletes) || !is_allowed_channel(&ctx, &channel, &settings).await {
         return;
     }
 
-    let log_channel = match get_channel_from_id(&ctx, guild_id, settings.log_channel_id).await {
+    let log_channel = match get_log_channel(ctx, pool, guild_id, &settings, LogEvent::Deletes).await
+    {
         Ok(c) => c,
         Err(_) => return,
     };
@@ -418,15 +906,69 @@ async fn uncached_message_handler(ctx: &Context, channel_id: ChannelId, message_
     }
 }
 
+/// A deleted message, from the cache or the archive.
+struct KnownMessage {
+    author_id: i64,
+    author_tag: String,
+    created_at: DateTime<Utc>,
+    content: String,
+    attachments: Vec<String>,
+}
+
+/// Writes the messages of a bulk delete as text, grouped by author.
+fn bulk_delete_text(
+    channel_name: &str,
+    total: usize,
+    known: usize,
+    mut messages: Vec<KnownMessage>,
+) -> String {
+    messages.sort_by(|a, b| {
+        (&a.author_tag, a.author_id, a.created_at).cmp(&(&b.author_tag, b.author_id, b.created_at))
+    });
+
+    let mut text = format!(
+        "{} messages were deleted in #{}. {} of them are known.\n",
+        total, channel_name, known
+    );
+
+    let mut last_author = None;
+    for message in messages {
+        if last_author != Some(message.author_id) {
+            let _ = write!(
+                text,
+                "\n== {} ({}) ==\n",
+                message.author_tag, message.author_id
+            );
+            last_author = Some(message.author_id);
+        }
+
+        let _ = writeln!(
+            text,
+            "[{}] {}",
+            message.created_at.format("%F %T UTC"),
+            message.content
+        );
+        for attachment in message.attachments {
+            let _ = writeln!(text, "    Attachment: {}", attachment);
+        }
+    }
+
+    text
+}
+
 fn get_added_fields_and_file<'a>(
     content: &'a str,
     embed: &mut EmbedBuilder,
     iden: &'a str,
 ) -> Option<AttachmentType<'a>> {
     if content.len() > 1024 {
+        let end = content
+            .char_indices()
+            .nth(500)
+            .map_or(content.len(), |(i, _)| i);
         let text = format!(
             "{}...\n\nFull message attached below.",
-            content[..500].trim()
+            content[..end].trim()
         );
         embed.add_field((format!("{} Content", iden), text, true));
 
@@ -442,6 +984,66 @@ fn get_added_fields_and_file<'a>(
     }
 }
 
+/// Gets the log settings of a guild and its log channel, if `event` is logged.
+pub(crate) async fn get_event_log(
+    ctx: &Context,
+    guild_id: GuildId,
+    event: LogEvent,
+) -> Option<(LogSettings, GuildChannel)> {
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let settings = sqlx::query_as!(
+        LogSettings,
+        "SELECT * FROM logging WHERE guild_id = $1;",
+        guild_id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten()?;
+
+    if !settings.logs(event) {
+        return None;
+    }
+
+    let log_channel = get_log_channel(ctx, pool, guild_id, &settings, event)
+        .await
+        .ok()?;
+
+    Some((settings, log_channel))
+}
+
+/// Gets the channel `event` is logged in.
+///
+/// Events without a channel of their own, or whose channel was deleted, are
+/// logged in the default log channel.
+pub(crate) async fn get_log_channel(
+    ctx: &Context,
+    pool: &PgPool,
+    guild_id: GuildId,
+    settings: &LogSettings,
+    event: LogEvent,
+) -> Result<GuildChannel, &'static str> {
+    let route = sqlx::query!(
+        "SELECT channel_id FROM log_routes WHERE guild_id = $1 AND event = $2;",
+        guild_id.0 as i64,
+        event.name()
+    )
+    .fetch_optional(pool)
+    .await
+    .ok()
+    .flatten();
+
+    if let Some(r) = route {
+        if let Ok(c) = get_channel_from_id(ctx, guild_id, Some(r.channel_id)).await {
+            return Ok(c);
+        }
+    }
+
+    get_channel_from_id(ctx, guild_id, settings.log_channel_id).await
+}
+
 pub(crate) async fn is_allowed_channel(
     ctx: &Context,
     channel: &GuildChannel,
@@ -0,0 +1,215 @@
+// This module detects ghost pings.
+//
+// A ghost ping happens when someone mentions a member or a role and then
+// deletes the message, or edits the mention out. The pinged members get a
+// notification but can't see who pinged them. Alerts are sent in the log
+// channel, or in the channel of the message if the server prefers that.
+
+use crate::events::{get_log_channel, LogEvent, LogSettings};
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serenity::{
+    model::prelude::{ChannelId, GuildId, Message, RoleId, UserId},
+    prelude::Context,
+};
+use sqlx::PgPool;
+use tracing::{error, instrument};
+
+static MENTION_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<@(!|&)?(\d+)>").unwrap());
+
+/// Members and roles mentioned in a message.
+#[derive(Debug, Default)]
+pub(crate) struct Pings {
+    users: Vec<UserId>,
+    roles: Vec<RoleId>,
+}
+
+/// The message a ghost ping was found in.
+#[derive(Debug)]
+pub(crate) struct PingedMessage<'a> {
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    pub author_id: UserId,
+    pub author_tag: &'a str,
+    pub content: &'a str,
+}
+
+impl Pings {
+    /// Gets the mentions of a message, including the author of the message it
+    /// replies to.
+    pub(crate) fn from_message(msg: &Message) -> Self {
+        let mentions = Self {
+            users: msg.mentions.iter().map(|u| u.id).collect(),
+            roles: msg.mention_roles.clone(),
+        };
+
+        Self::from_content(&msg.content).merge(mentions)
+    }
+
+    /// Gets the mentions written in a message.
+    pub(crate) fn from_content(content: &str) -> Self {
+        let mut pings = Self::default();
+        for caps in MENTION_RE.captures_iter(content) {
+            let id = match caps[2].parse::<u64>() {
+                Ok(i) => i,
+                Err(_) => continue,
+            };
+
+            if caps.get(1).map(|m| m.as_str()) == Some("&") {
+                if !pings.roles.contains(&RoleId(id)) {
+                    pings.roles.push(RoleId(id));
+                }
+            } else if !pings.users.contains(&UserId(id)) {
+                pings.users.push(UserId(id));
+            }
+        }
+
+        pings
+    }
+
+    /// Adds the mentions of `other` that aren't in these.
+    pub(crate) fn merge(mut self, other: Pings) -> Self {
+        for user in other.users {
+            if !self.users.contains(&user) {
+                self.users.push(user);
+            }
+        }
+        for role in other.roles {
+            if !self.roles.contains(&role) {
+                self.roles.push(role);
+            }
+        }
+
+        self
+    }
+
+    /// Gets the mentions that aren't in `new` anymore.
+    pub(crate) fn removed(&self, new: &Pings) -> Self {
+        Self {
+            users: self
+                .users
+                .iter()
+                .filter(|u| !new.users.contains(u))
+                .copied()
+                .collect(),
+            roles: self
+                .roles
+                .iter()
+                .filter(|r| !new.roles.contains(r))
+                .copied()
+                .collect(),
+        }
+    }
+
+    fn mentions(&self) -> String {
+        self.users
+            .iter()
+            .map(|u| format!("<@{}>", u.0))
+            .chain(self.roles.iter().map(|r| format!("<@&{}>", r.0)))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+/// Sends an alert if a deleted or edited message pinged someone.
+///
+/// `pings` are the mentions the message lost.
+#[instrument(skip(ctx, pool))]
+pub(crate) async fn ghost_ping_handler(
+    ctx: &Context,
+    pool: &PgPool,
+    message: PingedMessage<'_>,
+    mut pings: Pings,
+    edited: bool,
+) {
+    // Pinging yourself doesn't bother anyone.
+    pings.users.retain(|u| *u != message.author_id);
+    if pings.users.is_empty() && pings.roles.is_empty() {
+        return;
+    }
+
+    let settings = match sqlx::query_as!(
+        LogSettings,
+        "SELECT * FROM logging WHERE guild_id = $1;",
+        message.guild_id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await
+    {
+        Ok(Some(s)) if s.logs(LogEvent::GhostPings) => s,
+        _ => return,
+    };
+
+    // Only the blacklist applies, since ghost pings are just as annoying in private channels.
+    if let Some(ids) = &settings.blacklist_channel_ids {
+        if ids.contains(&(message.channel_id.0 as i64)) {
+            return;
+        }
+    }
+
+    let in_channel = settings.ghost_pings_in_channel;
+    let alert_channel = if in_channel {
+        message.channel_id
+    } else {
+        match get_log_channel(ctx, pool, message.guild_id, &settings, LogEvent::GhostPings).await {
+            Ok(c) => c.id,
+            Err(_) => return,
+        }
+    };
+
+    let action = if edited { "edited" } else { "deleted" };
+    let description = format!(
+        "<@{}> pinged {} and then {} the message.",
+        message.author_id.0,
+        pings.mentions(),
+        action
+    );
+
+    // The message may have been deleted for a reason, so its content is only
+    // shown in the log channel.
+    let content = if in_channel || message.content.is_empty() {
+        None
+    } else {
+        let end = message
+            .content
+            .char_indices()
+            .nth(1000)
+            .map_or(message.content.len(), |(i, _)| i);
+        Some(message.content[..end].to_string())
+    };
+
+    let msg = alert_channel
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0xFF0000);
+                e.description(description);
+                e.timestamp(Utc::now().to_rfc3339());
+                if !in_channel {
+                    e.field("Channel", format!("<#{}>", message.channel_id.0), true);
+                }
+                if let Some(c) = content {
+                    e.field("Message", c, false);
+                }
+                e.footer(|f| {
+                    f.text(format!("Author ID: {}", message.author_id.0));
+
+                    f
+                });
+                e.author(|a| {
+                    a.name(format!("{} - Ghost Ping", message.author_tag));
+
+                    a
+                });
+
+                e
+            });
+
+            m
+        })
+        .await;
+
+    if let Err(why) = msg {
+        error!("Failed to send ghost ping alert: {}", why);
+    }
+}
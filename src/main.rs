@@ -1,10 +1,26 @@
+mod archive;
+mod audit;
+mod board;
 mod commands;
 mod events;
+mod ghost_pings;
+mod relay;
+mod server_events;
+mod tasks;
+mod template;
 mod utils;
+mod vote_changes;
 
+use archive::archive_message;
+use audit::record_command;
+use board::{board_delete_handler, board_member_update};
 use commands::{help::help_command, host::*, logging::*, meta::*, owner::*, setup::*, user::*};
 use dotenv::dotenv;
-use events::{message_delete_bulk_handler, message_delete_handler, message_update_handler};
+use events::{
+    get_message_settings, message_delete_bulk_handler, message_delete_handler,
+    message_update_handler,
+};
+use relay::{link_handler, message_handler};
 use serenity::{
     async_trait,
     client::bridge::gateway::{GatewayIntents, ShardManager},
@@ -19,10 +35,24 @@ use serenity::{
     model::{event::ResumedEvent, gateway::Ready, prelude::*},
     prelude::*,
 };
+use server_events::{
+    channel_update_handler, member_addition_handler, member_removal_handler, member_update_handler,
+};
 use sqlx::PgPool;
-use std::{collections::HashSet, env, sync::Arc};
+use std::{
+    collections::HashSet,
+    env,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tasks::start_tasks;
 use tracing::{error, info, instrument};
-use utils::database::{initialize_tables, obtain_pool, run_migrations};
+use utils::{
+    database::{initialize_tables, obtain_pool, run_migrations},
+    storage::Storage,
+};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
@@ -34,6 +64,9 @@ struct ConnectionPool;
 /// Asynchronous client to make HTTP requests.
 struct RequestClient;
 
+/// Storage for downloaded attachments.
+struct StorageContainer;
+
 impl TypeMapKey for ShardManagerContainer {
     type Value = Arc<Mutex<ShardManager>>;
 }
@@ -46,14 +79,25 @@ impl TypeMapKey for RequestClient {
     type Value = reqwest::Client;
 }
 
-struct Handler;
+impl TypeMapKey for StorageContainer {
+    type Value = Storage;
+}
+
+struct Handler {
+    // `ready` fires again on reconnects, but the tasks must only start once.
+    tasks_running: AtomicBool,
+}
 
 #[async_trait]
 impl EventHandler for Handler {
-    #[instrument(skip(self, ready))]
-    async fn ready(&self, _: Context, ready: Ready) {
+    #[instrument(skip(self, ctx, ready))]
+    async fn ready(&self, ctx: Context, ready: Ready) {
         info!("Connected as {}", ready.user.name);
         info!("Version {}", VERSION);
+
+        if !self.tasks_running.swap(true, Ordering::Relaxed) {
+            start_tasks(ctx);
+        }
     }
 
     #[instrument(skip(self))]
@@ -69,6 +113,59 @@ impl EventHandler for Handler {
         }
     }
 
+    #[instrument(skip(self, ctx))]
+    async fn guild_member_update(&self, ctx: Context, old: Option<Member>, new: Member) {
+        // Role changes made by hosts by hand should show up on the sign-up board too.
+        board_member_update(&ctx, old.as_ref(), &new).await;
+
+        member_update_handler(&ctx, old, &new).await;
+    }
+
+    #[instrument(skip(self, ctx))]
+    async fn guild_member_addition(&self, ctx: Context, _: GuildId, new_member: Member) {
+        member_addition_handler(&ctx, &new_member).await;
+    }
+
+    #[instrument(skip(self, ctx))]
+    async fn guild_member_removal(
+        &self,
+        ctx: Context,
+        guild_id: GuildId,
+        user: User,
+        member_data_if_available: Option<Member>,
+    ) {
+        member_removal_handler(&ctx, guild_id, &user, member_data_if_available).await;
+    }
+
+    #[instrument(skip(self, ctx))]
+    async fn channel_update(&self, ctx: Context, old: Option<Channel>, new: Channel) {
+        channel_update_handler(&ctx, old, &new).await;
+    }
+
+    #[instrument(skip(self, ctx))]
+    async fn message(&self, ctx: Context, msg: Message) {
+        let guild_id = match msg.guild_id {
+            Some(i) => i,
+            None => return,
+        };
+
+        let settings = {
+            let data_read = ctx.data.read().await;
+            let pool = data_read.get::<ConnectionPool>().unwrap();
+
+            match get_message_settings(pool, guild_id, msg.channel_id).await {
+                Some(s) => s,
+                None => return,
+            }
+        };
+
+        // Relays go first, so they aren't held up by the archive.
+        message_handler(&ctx, &msg, &settings.anon).await;
+        link_handler(&ctx, &msg, settings.link.as_ref()).await;
+        let votes_channel_id = settings.anon.cycle.as_ref().and_then(|c| c.0.votes);
+        archive_message(&ctx, &msg, settings.log.as_ref(), votes_channel_id).await;
+    }
+
     #[instrument(skip(self, ctx))]
     async fn message_update(
         &self,
@@ -86,8 +183,9 @@ impl EventHandler for Handler {
         ctx: Context,
         channel_id: ChannelId,
         deleted_message_id: MessageId,
-        _: Option<GuildId>,
+        guild_id: Option<GuildId>,
     ) {
+        board_delete_handler(&ctx, guild_id, deleted_message_id).await;
         message_delete_handler(ctx, channel_id, deleted_message_id).await;
     }
 
@@ -172,6 +270,8 @@ async fn on_dispatch_error(ctx: &Context, msg: &Message, error: DispatchError) {
 #[hook]
 #[instrument]
 async fn after(ctx: &Context, msg: &Message, cmd_name: &str, error: CommandResult) {
+    record_command(ctx, msg, cmd_name, &error).await;
+
     if let Err(why) = &error {
         error!("Error while running command {}", &cmd_name);
         error!("{:?}", &error);
@@ -259,7 +359,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut client = Client::builder(&token)
         .framework(framework)
-        .event_handler(Handler)
+        .event_handler(Handler {
+            tasks_running: AtomicBool::new(false),
+        })
         .intents({
             let mut intents = GatewayIntents::all();
             intents.remove(GatewayIntents::DIRECT_MESSAGE_TYPING);
@@ -285,6 +387,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         // Add reqwest client to the data.
         let client = reqwest::Client::new();
         data.insert::<RequestClient>(client);
+
+        // Add the attachment storage to the data.
+        let storage = Storage::from_env().expect("Invalid storage in the environment.");
+        data.insert::<StorageContainer>(storage);
     }
 
     let shard_manager = client.shard_manager.clone();
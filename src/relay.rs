@@ -0,0 +1,323 @@
+// This module relays messages from private chats to other channels.
+//
+// In anonymous games, players post in their private chat with a prefix and
+// the message is re-posted in the day or voting channel through a webhook,
+// under the player's alias.
+//
+// Hosts can also link two channels for one phase. Messages in either channel
+// are copied to the other one, under the author's name or anonymously.
+
+use crate::{
+    commands::{
+        setup::Cycle,
+        user::{is_day, is_private_chat},
+    },
+    ConnectionPool,
+};
+use serenity::{
+    model::{
+        prelude::{ChannelId, GuildChannel, GuildId, Message, RoleId},
+        webhook::Webhook,
+    },
+    prelude::Context,
+    utils::{content_safe, ContentSafeOptions},
+};
+use sqlx::{types::Json, PgPool};
+use tracing::{error, instrument};
+
+/// Name of the webhooks created by the bot.
+const WEBHOOK_NAME: &str = "TvM Relay";
+
+/// Name shown on messages relayed through an anonymous link.
+const ANONYMOUS_NAME: &str = "Anonymous";
+
+/// Most characters Discord allows in a message.
+const MESSAGE_LIMIT: usize = 2000;
+
+/// Settings of a guild for anonymous mode.
+pub(crate) struct AnonSettings {
+    pub anonymous: bool,
+    pub anon_prefix: String,
+    pub player_role_id: Option<i64>,
+    pub cycle: Option<Json<Cycle>>,
+}
+
+/// The link a channel is part of.
+pub(crate) struct ChannelLink {
+    pub first_channel_id: i64,
+    pub second_channel_id: i64,
+    pub first_name: Option<String>,
+    pub anonymous_links: bool,
+}
+
+#[instrument(skip(ctx, msg, settings))]
+pub(crate) async fn message_handler(ctx: &Context, msg: &Message, settings: &AnonSettings) {
+    if msg.author.bot || msg.webhook_id.is_some() || !settings.anonymous {
+        return;
+    }
+
+    let guild_id = match msg.guild_id {
+        Some(i) => i,
+        None => return,
+    };
+
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    // Messages look like `!day Hello` or `!vote VTL Alias`.
+    let rest = match msg.content.strip_prefix(settings.anon_prefix.as_str()) {
+        Some(r) => r,
+        None => return,
+    };
+    let (keyword, text) = match rest.split_once(char::is_whitespace) {
+        Some((k, t)) => (k.to_lowercase(), t.trim()),
+        None => return,
+    };
+
+    let cycle = match &settings.cycle {
+        Some(c) => &c.0,
+        None => return,
+    };
+    let target_id = match keyword.as_str() {
+        "day" => cycle.day,
+        "vote" => cycle.votes,
+        _ => return,
+    };
+
+    if text.is_empty() {
+        return;
+    }
+
+    let channel = match ctx.cache.guild_channel(msg.channel_id).await {
+        Some(c) => c,
+        None => return,
+    };
+    if !is_private_chat(&channel, msg.author.id) {
+        return;
+    }
+
+    let reply = |text: &'static str| async move {
+        let _ = msg.channel_id.say(&ctx.http, text).await;
+    };
+
+    let alias = match sqlx::query!(
+        "SELECT alias, avatar_url FROM aliases WHERE guild_id = $1 AND user_id = $2;",
+        guild_id.0 as i64,
+        msg.author.id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await
+    {
+        Ok(Some(a)) => a,
+        Ok(None) => {
+            reply("You don't have an alias yet. Please ask a host to give you one.").await;
+            return;
+        }
+        Err(why) => {
+            error!("Unable to fetch alias: {}", why);
+            return;
+        }
+    };
+
+    let player_role_id = match settings.player_role_id {
+        Some(i) => RoleId(i as u64),
+        None => return,
+    };
+    let alive = match msg.member(ctx).await {
+        Ok(m) => m.roles.contains(&player_role_id),
+        Err(_) => false,
+    };
+    if !alive {
+        reply("Only players who are alive can talk in the game channels.").await;
+        return;
+    }
+
+    let target = match target_id {
+        Some(i) => ctx.cache.guild_channel(i as u64).await,
+        None => None,
+    };
+    let target = match target {
+        Some(c) => c,
+        None => {
+            reply("I couldn't find that channel for the current cycle.").await;
+            return;
+        }
+    };
+
+    let player_role = match ctx.cache.role(guild_id, player_role_id).await {
+        Some(r) => r,
+        None => return,
+    };
+    if !is_day(&target, &player_role) {
+        reply("That channel is closed right now.").await;
+        return;
+    }
+
+    let options = ContentSafeOptions::new().display_as_member_from(guild_id);
+    let text = content_safe(&ctx.cache, text, &options).await;
+
+    let webhook = match get_webhook(ctx, &target).await {
+        Ok(w) => w,
+        Err(why) => {
+            error!("Unable to get a webhook for relaying: {}", why);
+            reply("I couldn't relay your message. Please check my permissions.").await;
+            return;
+        }
+    };
+
+    let res = webhook
+        .execute(&ctx.http, false, |w| {
+            w.username(&alias.alias).content(&text);
+            if let Some(url) = &alias.avatar_url {
+                w.avatar_url(url);
+            }
+            w
+        })
+        .await;
+
+    match res {
+        Ok(_) => {
+            let _ = msg.react(&ctx.http, '✅').await;
+        }
+        Err(why) => {
+            error!("Unable to relay message: {}", why);
+            reply("I couldn't relay your message.").await;
+        }
+    }
+}
+
+/// Gets the bot's webhook in a channel, creating it if it doesn't exist.
+pub(crate) async fn get_webhook(
+    ctx: &Context,
+    channel: &GuildChannel,
+) -> serenity::Result<Webhook> {
+    let webhooks = channel.id.webhooks(&ctx.http).await?;
+
+    // Only webhooks created by the bot come with a token.
+    if let Some(w) = webhooks
+        .into_iter()
+        .find(|w| w.name.as_deref() == Some(WEBHOOK_NAME) && w.token.is_some())
+    {
+        return Ok(w);
+    }
+
+    channel.create_webhook(&ctx.http, WEBHOOK_NAME).await
+}
+
+/// Copies messages sent in a linked channel to the other channel of the link.
+#[instrument(skip(ctx, msg, link))]
+pub(crate) async fn link_handler(ctx: &Context, msg: &Message, link: Option<&ChannelLink>) {
+    if msg.author.bot || msg.webhook_id.is_some() {
+        return;
+    }
+
+    let guild_id = match msg.guild_id {
+        Some(i) => i,
+        None => return,
+    };
+
+    let link = match link {
+        Some(l) => l,
+        None => return,
+    };
+
+    let target_id = if link.first_channel_id == msg.channel_id.0 as i64 {
+        link.second_channel_id
+    } else {
+        link.first_channel_id
+    };
+    let target = match ctx.cache.guild_channel(target_id as u64).await {
+        Some(c) => c,
+        None => return,
+    };
+
+    let options = ContentSafeOptions::new().display_as_member_from(guild_id);
+    let mut text = content_safe(&ctx.cache, &msg.content, &options).await;
+    let urls: String = msg
+        .attachments
+        .iter()
+        .map(|a| format!("\n{}", a.url))
+        .collect();
+
+    // Links to attachments are kept whole, so the text is cut to make room.
+    let room = MESSAGE_LIMIT.saturating_sub(urls.chars().count());
+    if text.chars().count() > room {
+        let end = text
+            .char_indices()
+            .nth(room.saturating_sub(3))
+            .map_or(text.len(), |(i, _)| i);
+        text.truncate(end);
+        text.push_str("...");
+    }
+    text.push_str(&urls);
+    if text.trim().is_empty() {
+        return;
+    }
+
+    let from_first = link.first_channel_id == msg.channel_id.0 as i64;
+    let (name, avatar_url) = if let (true, Some(name)) = (from_first, &link.first_name) {
+        (name.clone(), None)
+    } else if link.anonymous_links {
+        (ANONYMOUS_NAME.to_string(), None)
+    } else {
+        let name = msg
+            .author_nick(&ctx.http)
+            .await
+            .unwrap_or_else(|| msg.author.name.clone());
+        (name, Some(msg.author.face()))
+    };
+
+    let webhook = match get_webhook(ctx, &target).await {
+        Ok(w) => w,
+        Err(why) => {
+            error!("Unable to get a webhook for the channel link: {}", why);
+            return;
+        }
+    };
+
+    let res = webhook
+        .execute(&ctx.http, false, |w| {
+            w.username(&name).content(&text);
+            if let Some(url) = &avatar_url {
+                w.avatar_url(url);
+            }
+            w
+        })
+        .await;
+
+    if let Err(why) = res {
+        error!("Unable to relay message through channel link: {}", why);
+    }
+}
+
+/// Removes the channel links of a guild and lets both sides know.
+///
+/// It is called whenever the phase changes. Links made to last until the end
+/// of the cycle are only removed if `cycle_end` is `true`.
+pub(crate) async fn end_links(ctx: &Context, guild_id: GuildId, pool: &PgPool, cycle_end: bool) {
+    let links = match sqlx::query!(
+        "
+        DELETE FROM channel_links WHERE guild_id = $1 AND ($2 OR NOT until_cycle)
+        RETURNING first_channel_id, second_channel_id;
+        ",
+        guild_id.0 as i64,
+        cycle_end
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(l) => l,
+        Err(why) => {
+            error!("Unable to remove channel links: {}", why);
+            return;
+        }
+    };
+
+    for link in links {
+        for id in [link.first_channel_id, link.second_channel_id].iter() {
+            let _ = ChannelId(*id as u64)
+                .say(&ctx.http, "The link has ended.")
+                .await;
+        }
+    }
+}
@@ -0,0 +1,403 @@
+// This module contains functions to log member and channel events.
+//
+// Hosts use these logs as an audit trail of the game: who got the player or
+// dead role, who changed their nickname, who joined or left and how the
+// permissions of game channels changed. Each kind of event can be turned off
+// with `log event`.
+//
+// Only the game roles are logged, and permission changes are logged for the
+// channels the bot manages as well as logged channels.
+
+use crate::{
+    commands::setup::Cycle,
+    events::{get_event_log, is_allowed_channel, LogEvent},
+    template::GameRoles,
+    ConnectionPool,
+};
+use chrono::Utc;
+use serenity::{
+    model::prelude::{
+        Channel, ChannelId, GuildChannel, GuildId, Member, PermissionOverwrite,
+        PermissionOverwriteType, Permissions, RoleId, User,
+    },
+    prelude::Context,
+};
+use sqlx::{types::Json, PgPool};
+use std::fmt::Write;
+use tracing::{error, instrument};
+
+#[instrument(skip(ctx))]
+pub(crate) async fn member_update_handler(ctx: &Context, old: Option<Member>, new: &Member) {
+    // We can't tell what changed without the old member.
+    let old = match old {
+        Some(m) => m,
+        None => return,
+    };
+
+    let added: Vec<&RoleId> = new
+        .roles
+        .iter()
+        .filter(|r| !old.roles.contains(r))
+        .collect();
+    let removed: Vec<&RoleId> = old
+        .roles
+        .iter()
+        .filter(|r| !new.roles.contains(r))
+        .collect();
+
+    // Other roles of the server don't matter to the game.
+    let (added, removed) = if added.is_empty() && removed.is_empty() {
+        (added, removed)
+    } else {
+        let game_roles = {
+            let data_read = ctx.data.read().await;
+            let pool = data_read.get::<ConnectionPool>().unwrap();
+            match GameRoles::get(pool, new.guild_id).await {
+                Ok(r) => r.ids(),
+                Err(_) => Vec::new(),
+            }
+        };
+        let is_game_role = |r: &&RoleId| game_roles.contains(r);
+
+        (
+            added.into_iter().filter(is_game_role).collect(),
+            removed.into_iter().filter(is_game_role).collect(),
+        )
+    };
+
+    if !added.is_empty() || !removed.is_empty() {
+        let mut fields = Vec::new();
+        if !added.is_empty() {
+            fields.push(("Roles Added", mention_roles(&added), false));
+        }
+        if !removed.is_empty() {
+            fields.push(("Roles Removed", mention_roles(&removed), false));
+        }
+
+        send_member_log(ctx, new, LogEvent::Roles, 0x00CDFF, "Roles Changed", fields).await;
+    }
+
+    if old.nick != new.nick {
+        let nick = |n: &Option<String>| match n {
+            Some(n) => n.clone(),
+            None => "*None*".to_string(),
+        };
+        let fields = vec![
+            ("Before", nick(&old.nick), true),
+            ("After", nick(&new.nick), true),
+        ];
+
+        send_member_log(
+            ctx,
+            new,
+            LogEvent::Nicknames,
+            0xFF9300,
+            "Nickname Changed",
+            fields,
+        )
+        .await;
+    }
+}
+
+#[instrument(skip(ctx))]
+pub(crate) async fn member_addition_handler(ctx: &Context, member: &Member) {
+    let fields = vec![(
+        "Account Created",
+        member.user.created_at().format("%F %T UTC").to_string(),
+        true,
+    )];
+
+    send_member_log(
+        ctx,
+        member,
+        LogEvent::Joins,
+        0x00FF00,
+        "Member Joined",
+        fields,
+    )
+    .await;
+}
+
+#[instrument(skip(ctx))]
+pub(crate) async fn member_removal_handler(
+    ctx: &Context,
+    guild_id: GuildId,
+    user: &User,
+    member: Option<Member>,
+) {
+    let (_, log_channel) = match get_event_log(ctx, guild_id, LogEvent::Joins).await {
+        Some(l) => l,
+        None => return,
+    };
+
+    // Roles are only known if the member was cached.
+    let roles = match &member {
+        Some(m) if !m.roles.is_empty() => Some(mention_roles(&m.roles.iter().collect::<Vec<_>>())),
+        _ => None,
+    };
+
+    let msg = log_channel
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0xFF0000);
+                e.timestamp(Utc::now().to_rfc3339());
+                if let Some(r) = roles {
+                    e.field("Roles", r, false);
+                }
+                e.footer(|f| {
+                    f.text(format!("User ID: {}", user.id.0));
+
+                    f
+                });
+                e.author(|a| {
+                    a.name(format!("{} - Member Left", user.tag()));
+                    a.icon_url(user.face());
+
+                    a
+                });
+
+                e
+            });
+
+            m
+        })
+        .await;
+
+    if let Err(why) = msg {
+        error!("Failed to log member removal: {}", why);
+    }
+}
+
+#[instrument(skip(ctx))]
+pub(crate) async fn channel_update_handler(ctx: &Context, old: Option<Channel>, new: &Channel) {
+    let (old, new) = match (old.and_then(|c| c.guild()), new.clone().guild()) {
+        (Some(o), Some(n)) => (o, n),
+        _ => return,
+    };
+
+    let mut changes = overwrite_changes(&old.permission_overwrites, &new.permission_overwrites);
+    if changes.is_empty() {
+        return;
+    }
+    if let Some((end, _)) = changes.char_indices().nth(2000) {
+        changes.truncate(end);
+        changes.push_str("...");
+    }
+
+    let (settings, log_channel) = match get_event_log(ctx, new.guild_id, LogEvent::Overwrites).await
+    {
+        Some(l) => l,
+        None => return,
+    };
+
+    // Channels that were just hidden from everyone are logged too.
+    if !is_allowed_channel(ctx, &new, &settings).await
+        && !is_allowed_channel(ctx, &old, &settings).await
+    {
+        // Game channels are logged unless they are blacklisted.
+        let blacklisted = match &settings.blacklist_channel_ids {
+            Some(ids) => ids.contains(&(new.id.0 as i64)),
+            None => false,
+        };
+
+        let data_read = ctx.data.read().await;
+        let pool = data_read.get::<ConnectionPool>().unwrap();
+        if blacklisted || !is_game_channel(ctx, pool, &new).await {
+            return;
+        }
+    }
+
+    let msg = log_channel
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(0xFF9300);
+                e.description(changes);
+                e.timestamp(Utc::now().to_rfc3339());
+                e.field("Channel", format!("<#{}>", new.id.0), true);
+                e.author(|a| {
+                    a.name(format!("#{} - Permissions Changed", new.name));
+
+                    a
+                });
+                e.footer(|f| {
+                    f.text(format!("Channel ID: {}", new.id.0));
+
+                    f
+                });
+
+                e
+            });
+
+            m
+        })
+        .await;
+
+    if let Err(why) = msg {
+        error!("Failed to log permission changes: {}", why);
+    }
+}
+
+/// Checks whether a channel is one the bot manages for the game: a channel of
+/// the current cycle or in its category, in the game or private chat
+/// categories, the dead chat, or a stored player, faction or temporary chat.
+async fn is_game_channel(ctx: &Context, pool: &PgPool, channel: &GuildChannel) -> bool {
+    let res = sqlx::query!(
+        r#"
+        SELECT cycle AS "cycle: Json<Cycle>", game_category_id, dead_chat_id, pc_category_ids,
+        EXISTS(SELECT 1 FROM player_chats WHERE channel_id = $2)
+        OR EXISTS(SELECT 1 FROM faction_chats WHERE channel_id = $2)
+        OR EXISTS(SELECT 1 FROM temp_chats WHERE channel_id = $2) AS "stored!"
+        FROM config WHERE guild_id = $1;
+        "#,
+        channel.guild_id.0 as i64,
+        channel.id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await;
+
+    let res = match res {
+        Ok(Some(r)) => r,
+        Ok(None) => return false,
+        Err(why) => {
+            error!("Unable to fetch game channels: {}", why);
+            return false;
+        }
+    };
+
+    if res.stored {
+        return true;
+    }
+
+    let mut ids = Vec::new();
+    if let Some(cycle) = &res.cycle {
+        let cycle = &cycle.0;
+        ids.extend(
+            cycle
+                .channels
+                .iter()
+                .chain([cycle.day, cycle.night, cycle.votes].iter().flatten()),
+        );
+
+        // The cycle category is the one its channels are in.
+        for id in &cycle.channels {
+            if let Some(c) = ctx.cache.guild_channel(ChannelId(*id as u64)).await {
+                ids.extend(c.category_id.map(|i| i.0 as i64));
+                break;
+            }
+        }
+    }
+    ids.extend(res.game_category_id);
+    ids.extend(res.dead_chat_id);
+    ids.extend(res.pc_category_ids);
+
+    std::iter::once(channel.id)
+        .chain(channel.category_id)
+        .any(|i| ids.contains(&(i.0 as i64)))
+}
+
+/// Logs an event about a member in the log channel.
+async fn send_member_log(
+    ctx: &Context,
+    member: &Member,
+    event: LogEvent,
+    colour: u32,
+    title: &str,
+    fields: Vec<(&str, String, bool)>,
+) {
+    let (_, log_channel) = match get_event_log(ctx, member.guild_id, event).await {
+        Some(l) => l,
+        None => return,
+    };
+
+    let msg = log_channel
+        .send_message(&ctx.http, |m| {
+            m.embed(|e| {
+                e.colour(colour);
+                e.description(format!("<@{}>", member.user.id.0));
+                e.timestamp(Utc::now().to_rfc3339());
+                e.fields(fields);
+                e.footer(|f| {
+                    f.text(format!("User ID: {}", member.user.id.0));
+
+                    f
+                });
+                e.author(|a| {
+                    a.name(format!("{} - {}", member.user.tag(), title));
+                    a.icon_url(member.user.face());
+
+                    a
+                });
+
+                e
+            });
+
+            m
+        })
+        .await;
+
+    if let Err(why) = msg {
+        error!("Failed to log {}: {}", event.name(), why);
+    }
+}
+
+fn mention_roles(roles: &[&RoleId]) -> String {
+    roles
+        .iter()
+        .map(|r| format!("<@&{}>", r.0))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Describes how the permission overwrites of a channel changed.
+fn overwrite_changes(old: &[PermissionOverwrite], new: &[PermissionOverwrite]) -> String {
+    let mut text = String::new();
+
+    let mut kinds: Vec<PermissionOverwriteType> = old.iter().map(|o| o.kind).collect();
+    kinds.extend(
+        new.iter()
+            .map(|o| o.kind)
+            .filter(|k| !old.iter().any(|o| o.kind == *k)),
+    );
+
+    for kind in kinds {
+        let find = |overwrites: &[PermissionOverwrite]| {
+            overwrites
+                .iter()
+                .find(|o| o.kind == kind)
+                .map_or((Permissions::empty(), Permissions::empty()), |o| {
+                    (o.allow, o.deny)
+                })
+        };
+        let (old_allow, old_deny) = find(old);
+        let (new_allow, new_deny) = find(new);
+
+        if old_allow == new_allow && old_deny == new_deny {
+            continue;
+        }
+
+        let target = match kind {
+            PermissionOverwriteType::Member(id) => format!("<@{}>", id.0),
+            PermissionOverwriteType::Role(id) => format!("<@&{}>", id.0),
+            _ => continue,
+        };
+        let _ = write!(text, "\n**{}**", target);
+
+        let allowed = new_allow - old_allow;
+        let denied = new_deny - old_deny;
+        let reset = (old_allow | old_deny) - (new_allow | new_deny);
+        for (label, permissions) in
+            [("Allowed", allowed), ("Denied", denied), ("Reset", reset)].iter()
+        {
+            if !permissions.is_empty() {
+                let _ = write!(
+                    text,
+                    "\n{}: {}",
+                    label,
+                    permissions.get_permission_names().join(", ")
+                );
+            }
+        }
+    }
+
+    text.trim_start().to_string()
+}
@@ -0,0 +1,193 @@
+// This module contains background tasks which run periodically.
+//
+// The tasks are started once, when the bot first becomes ready. Everything
+// they act on is stored in the database, so nothing is lost on restarts.
+
+use crate::{
+    archive::attachment_key,
+    commands::{
+        host::{send_ready_results, ReadyCheck},
+        setup::set_signups,
+    },
+    ConnectionPool, StorageContainer,
+};
+use serenity::{model::prelude::GuildId, prelude::Context};
+use std::time::Duration;
+use tracing::{error, instrument};
+
+/// How often the scheduled tasks are checked.
+const TASK_INTERVAL: Duration = Duration::from_secs(30);
+
+pub(crate) fn start_tasks(ctx: Context) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TASK_INTERVAL);
+        loop {
+            interval.tick().await;
+            run_scheduled_signups(&ctx).await;
+            remove_expired_bans(&ctx).await;
+            end_ready_checks(&ctx).await;
+            prune_message_archive(&ctx).await;
+            prune_stored_attachments(&ctx).await;
+        }
+    });
+}
+
+#[instrument(skip(ctx))]
+async fn run_scheduled_signups(ctx: &Context) {
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    // Rows are removed as they are picked up, so an action can't run twice.
+    let due = match sqlx::query!(
+        "DELETE FROM signup_schedule WHERE run_at <= now() RETURNING guild_id, action;"
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(r) => r,
+        Err(why) => {
+            error!("Unable to fetch scheduled sign-ups: {}", why);
+            return;
+        }
+    };
+
+    for row in due {
+        let open = row.action == "open";
+        let announcement = if open {
+            "**Sign-ups are now open!**"
+        } else {
+            "**Sign-ups are now closed.**"
+        };
+
+        if let Err(why) =
+            set_signups(ctx, pool, GuildId(row.guild_id as u64), open, announcement).await
+        {
+            error!(
+                "Unable to {} sign-ups for guild with ID `{}`: {}",
+                row.action, row.guild_id, why
+            );
+        }
+    }
+}
+
+#[instrument(skip(ctx))]
+async fn remove_expired_bans(ctx: &Context) {
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    if let Err(why) = sqlx::query!("DELETE FROM game_bans WHERE expires_at <= now();")
+        .execute(pool)
+        .await
+    {
+        error!("Unable to remove expired game bans: {}", why);
+    }
+}
+
+#[instrument(skip(ctx))]
+async fn end_ready_checks(ctx: &Context) {
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    // Checks are marked as ended as they are picked up, so results are only posted once.
+    let due = match sqlx::query_as!(
+        ReadyCheck,
+        "
+        UPDATE ready_checks SET ended = true
+        WHERE NOT ended AND ends_at <= now()
+        RETURNING guild_id, channel_id, message_id, results_channel_id, players, confirmed;
+        "
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(r) => r,
+        Err(why) => {
+            error!("Unable to fetch ended ready checks: {}", why);
+            return;
+        }
+    };
+
+    for check in due {
+        let guild_id = check.guild_id;
+        // Players who hadn't confirmed when the check was picked up.
+        let no_shows: Vec<i64> = check
+            .players
+            .iter()
+            .filter(|p| !check.confirmed.contains(p))
+            .copied()
+            .collect();
+
+        if let Err(why) = send_ready_results(ctx, pool, check).await {
+            error!(
+                "Unable to post ready check results for guild with ID `{}`: {}",
+                guild_id, why
+            );
+
+            // The check has ended either way, so `readydrop` shouldn't wait for it.
+            let res = sqlx::query!(
+                "UPDATE ready_checks SET no_shows = $2 WHERE guild_id = $1 AND no_shows IS NULL;",
+                guild_id,
+                &no_shows
+            )
+            .execute(pool)
+            .await;
+
+            if let Err(why) = res {
+                error!("Unable to end ready check: {}", why);
+            }
+        }
+    }
+}
+
+#[instrument(skip(ctx))]
+async fn prune_message_archive(ctx: &Context) {
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+
+    let res = sqlx::query!(
+        "
+        DELETE FROM message_archive AS a USING logging AS l
+        WHERE a.guild_id = l.guild_id
+        AND a.created_at < now() - make_interval(days => l.archive_retention);
+        "
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(why) = res {
+        error!("Unable to prune message archive: {}", why);
+    }
+}
+
+#[instrument(skip(ctx))]
+async fn prune_stored_attachments(ctx: &Context) {
+    let data_read = ctx.data.read().await;
+    let pool = data_read.get::<ConnectionPool>().unwrap();
+    let storage = data_read.get::<StorageContainer>().unwrap();
+
+    // Rows are removed first, so a file that can't be deleted isn't tried again.
+    let expired = match sqlx::query!(
+        "
+        DELETE FROM stored_attachments AS a USING logging AS l
+        WHERE a.guild_id = l.guild_id
+        AND a.created_at < now() - make_interval(days => l.attachment_retention)
+        RETURNING a.guild_id, a.message_id, a.attachment_id;
+        "
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(r) => r,
+        Err(why) => {
+            error!("Unable to prune stored attachments: {}", why);
+            return;
+        }
+    };
+
+    for row in expired {
+        let key = attachment_key(row.guild_id, row.message_id, row.attachment_id);
+        if let Err(why) = storage.delete(&key).await {
+            error!("Unable to delete stored attachment `{}`: {}", key, why);
+        }
+    }
+}
@@ -0,0 +1,336 @@
+// This module defines cycle templates.
+//
+// A template decides which channels the `cycle` command creates, what they
+// are called, and who can see and talk in them during the day and the night.
+// Servers without a template of their own use the default one, which creates
+// the usual `day-x`, `day-x-voting` and `night-x` channels.
+
+use serde::{Deserialize, Serialize};
+use serenity::model::prelude::{
+    Guild, GuildId, PermissionOverwrite, PermissionOverwriteType, Permissions, RoleId, UserId,
+};
+use sqlx::{types::Json, PgPool};
+
+/// Maximum slowmode Discord allows, in seconds.
+const MAX_SLOWMODE: u64 = 21600;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CycleTemplate {
+    /// Name of the category. `{n}` is replaced by the cycle number.
+    #[serde(default = "default_category")]
+    pub category: String,
+    pub channels: Vec<TemplateChannel>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TemplateChannel {
+    /// Name of the channel. `{n}` is replaced by the cycle number.
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    /// Slowmode of the channel, in seconds.
+    #[serde(default)]
+    pub slowmode: u64,
+    /// What the bot uses the channel for.
+    #[serde(default, rename = "use")]
+    pub uses: Vec<ChannelUse>,
+    #[serde(default)]
+    pub day: PhaseAccess,
+    #[serde(default)]
+    pub night: PhaseAccess,
+}
+
+/// Who can see and talk in a channel during a phase.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct PhaseAccess {
+    /// Whether everyone can see the channel.
+    #[serde(default)]
+    pub visible: bool,
+    /// Game roles that can talk in the channel.
+    #[serde(default)]
+    pub write: Vec<GameRole>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ChannelUse {
+    Day,
+    Votes,
+    Night,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum GameRole {
+    Hosts,
+    Players,
+    Spectators,
+    Replacements,
+    Dead,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Phase {
+    Day,
+    Night,
+}
+
+/// IDs of the game roles of a guild.
+pub(crate) struct GameRoles {
+    pub host_role_id: Option<i64>,
+    pub player_role_id: Option<i64>,
+    pub spec_role_id: Option<i64>,
+    pub repl_role_id: Option<i64>,
+    pub dead_role_id: Option<i64>,
+}
+
+impl GameRoles {
+    pub(crate) async fn get(pool: &PgPool, guild_id: GuildId) -> Result<Self, sqlx::Error> {
+        sqlx::query_as!(
+            GameRoles,
+            "
+            SELECT host_role_id, player_role_id, spec_role_id, repl_role_id, dead_role_id
+            FROM config WHERE guild_id = $1;
+            ",
+            guild_id.0 as i64
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// IDs of all game roles that are set.
+    pub(crate) fn ids(&self) -> Vec<RoleId> {
+        [
+            GameRole::Hosts,
+            GameRole::Players,
+            GameRole::Spectators,
+            GameRole::Replacements,
+            GameRole::Dead,
+        ]
+        .iter()
+        .filter_map(|r| self.id(*r))
+        .collect()
+    }
+
+    fn id(&self, role: GameRole) -> Option<RoleId> {
+        let id = match role {
+            GameRole::Hosts => self.host_role_id,
+            GameRole::Players => self.player_role_id,
+            GameRole::Spectators => self.spec_role_id,
+            GameRole::Replacements => self.repl_role_id,
+            GameRole::Dead => self.dead_role_id,
+        };
+
+        id.map(|i| RoleId(i as u64))
+    }
+}
+
+fn default_category() -> String {
+    String::from("Cycle {n}")
+}
+
+impl Default for CycleTemplate {
+    fn default() -> Self {
+        let open = PhaseAccess {
+            visible: true,
+            write: vec![GameRole::Players],
+        };
+        let closed = PhaseAccess {
+            visible: true,
+            write: Vec::new(),
+        };
+
+        Self {
+            category: default_category(),
+            channels: vec![
+                TemplateChannel {
+                    name: String::from("day-{n}"),
+                    topic: None,
+                    slowmode: 0,
+                    uses: vec![ChannelUse::Day],
+                    day: open.clone(),
+                    night: closed.clone(),
+                },
+                TemplateChannel {
+                    name: String::from("day-{n}-voting"),
+                    topic: None,
+                    slowmode: 0,
+                    uses: vec![ChannelUse::Votes],
+                    day: open.clone(),
+                    night: closed,
+                },
+                TemplateChannel {
+                    name: String::from("night-{n}"),
+                    topic: None,
+                    slowmode: 0,
+                    uses: vec![ChannelUse::Night],
+                    day: PhaseAccess::default(),
+                    night: open,
+                },
+            ],
+        }
+    }
+}
+
+impl CycleTemplate {
+    /// Gets the cycle template of a guild, or the default one if it has none.
+    pub(crate) async fn get(pool: &PgPool, guild_id: GuildId) -> Self {
+        let res = sqlx::query!(
+            r#"SELECT cycle_template AS "cycle_template: Json<CycleTemplate>" FROM config WHERE guild_id = $1;"#,
+            guild_id.0 as i64
+        )
+        .fetch_optional(pool)
+        .await;
+
+        match res {
+            Ok(Some(r)) => match r.cycle_template {
+                Some(t) => t.0,
+                None => Self::default(),
+            },
+            _ => Self::default(),
+        }
+    }
+
+    /// Checks that channels can be created from the template.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.channels.is_empty() {
+            return Err("The template must have at least one channel.".to_string());
+        }
+        if self.channels.len() > 50 {
+            return Err("A category can't have more than 50 channels.".to_string());
+        }
+        if fill(&self.category, 1).trim().is_empty() {
+            return Err("The category name can't be empty.".to_string());
+        }
+
+        for channel_use in [ChannelUse::Day, ChannelUse::Votes, ChannelUse::Night].iter() {
+            let count = self
+                .channels
+                .iter()
+                .filter(|c| c.uses.contains(channel_use))
+                .count();
+            if count > 1 {
+                return Err(format!(
+                    "Only one channel can be used as the `{}` channel.",
+                    channel_use.name()
+                ));
+            }
+        }
+
+        for channel in &self.channels {
+            if fill(&channel.name, 1).trim().is_empty() {
+                return Err("Channel names can't be empty.".to_string());
+            }
+            if channel.slowmode > MAX_SLOWMODE {
+                return Err(format!(
+                    "Slowmode can't be longer than {} seconds.",
+                    MAX_SLOWMODE
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finds a game role the template uses that isn't set or doesn't exist.
+    pub(crate) fn missing_role(&self, roles: &GameRoles, guild: &Guild) -> Option<GameRole> {
+        self.channels
+            .iter()
+            .flat_map(|c| c.day.write.iter().chain(c.night.write.iter()))
+            .find(|r| match roles.id(**r) {
+                Some(id) => !guild.roles.contains_key(&id),
+                None => true,
+            })
+            .copied()
+    }
+
+    /// Gets the channel used for `channel_use`, if there is one.
+    pub(crate) fn position(&self, channel_use: ChannelUse) -> Option<usize> {
+        self.channels
+            .iter()
+            .position(|c| c.uses.contains(&channel_use))
+    }
+}
+
+impl TemplateChannel {
+    /// Gets the permission overwrites of the channel during a phase.
+    pub(crate) fn overwrites(
+        &self,
+        phase: Phase,
+        roles: &GameRoles,
+        guild_id: GuildId,
+        bot_id: UserId,
+    ) -> Vec<PermissionOverwrite> {
+        let access = match phase {
+            Phase::Day => &self.day,
+            Phase::Night => &self.night,
+        };
+
+        let mut overwrites = vec![
+            if access.visible {
+                PermissionOverwrite {
+                    allow: Permissions::READ_MESSAGES | Permissions::ADD_REACTIONS,
+                    deny: Permissions::SEND_MESSAGES,
+                    kind: PermissionOverwriteType::Role(RoleId(guild_id.0)),
+                }
+            } else {
+                PermissionOverwrite {
+                    allow: Permissions::empty(),
+                    deny: Permissions::READ_MESSAGES | Permissions::SEND_MESSAGES,
+                    kind: PermissionOverwriteType::Role(RoleId(guild_id.0)),
+                }
+            },
+            PermissionOverwrite {
+                allow: Permissions::READ_MESSAGES
+                    | Permissions::SEND_MESSAGES
+                    | Permissions::EMBED_LINKS,
+                deny: Permissions::empty(),
+                kind: PermissionOverwriteType::Member(bot_id),
+            },
+        ];
+
+        for role in &access.write {
+            if let Some(id) = roles.id(*role) {
+                overwrites.push(PermissionOverwrite {
+                    allow: Permissions::READ_MESSAGES | Permissions::SEND_MESSAGES,
+                    deny: Permissions::ATTACH_FILES,
+                    kind: PermissionOverwriteType::Role(id),
+                });
+            }
+        }
+
+        overwrites
+    }
+}
+
+impl GameRole {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::Hosts => "hosts",
+            Self::Players => "players",
+            Self::Spectators => "spectators",
+            Self::Replacements => "replacements",
+            Self::Dead => "dead",
+        }
+    }
+}
+
+impl ChannelUse {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Votes => "votes",
+            Self::Night => "night",
+        }
+    }
+}
+
+/// Replaces the placeholders in a name pattern.
+///
+/// `{n}` is replaced by the cycle number.
+pub(crate) fn fill(pattern: &str, number: i16) -> String {
+    pattern.replace("{n}", &number.to_string())
+}
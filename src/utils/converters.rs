@@ -1,6 +1,8 @@
 //! Functions and wrappers to get `Role`, Member`, etc., from
 //! user input and/or database values.
 
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
 use serenity::{
     model::{channel::GuildChannel, prelude::*},
     prelude::*,
@@ -143,3 +145,33 @@ pub async fn get_channel_from_id(
         None => Err("No channel was found from the given input."),
     }
 }
+
+/// Parses a date (`YYYY-MM-DD`), a time (`HH:MM`) and an optional timezone
+/// into a UTC `DateTime`.
+///
+/// The timezone must be a name from the tz database, like `Europe/London`.
+/// UTC is used if no timezone is given.
+pub fn parse_datetime(
+    date: &str,
+    time: &str,
+    timezone: Option<&str>,
+) -> Result<DateTime<Utc>, &'static str> {
+    let naive = match NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M")
+    {
+        Ok(n) => n,
+        Err(_) => return Err("Date and time must look like `2021-05-02 18:30`."),
+    };
+
+    let tz: Tz = match timezone {
+        Some(t) => match t.parse() {
+            Ok(tz) => tz,
+            Err(_) => return Err("Timezone must be a name like `Europe/London` or `UTC`."),
+        },
+        None => Tz::UTC,
+    };
+
+    match tz.from_local_datetime(&naive).earliest() {
+        Some(dt) => Ok(dt.with_timezone(&Utc)),
+        None => Err("That time doesn't exist in the given timezone."),
+    }
+}
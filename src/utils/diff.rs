@@ -0,0 +1,284 @@
+//! Compare two versions of a text, like the content of an edited message.
+
+use std::fmt::Write;
+
+/// How many unchanged lines are shown around changes in a unified diff.
+const CONTEXT_LINES: usize = 3;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Same,
+    Removed,
+    Added,
+}
+
+/// Finds the shortest way to turn `old` into `new`, using the longest common
+/// subsequence of both.
+fn diff<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Op, &'a str)> {
+    // Most edits change a small part of a message, so the unchanged start and
+    // end are left out of the table below to keep it small.
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+
+    let mut ops: Vec<(Op, &str)> = old[..prefix].iter().map(|t| (Op::Same, *t)).collect();
+    ops.extend(changes(&old[prefix..old_end], &new[prefix..new_end]));
+    ops.extend(old[old_end..].iter().map(|t| (Op::Same, *t)));
+
+    ops
+}
+
+fn changes<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Op, &'a str)> {
+    // `lengths[i][j]` is the length of the longest common subsequence of
+    // `old[i..]` and `new[j..]`.
+    let mut lengths = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut ops = Vec::new();
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            ops.push((Op::Same, old[i]));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            ops.push((Op::Removed, old[i]));
+            i += 1;
+        } else {
+            ops.push((Op::Added, new[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[i..].iter().map(|t| (Op::Removed, *t)));
+    ops.extend(new[j..].iter().map(|t| (Op::Added, *t)));
+
+    ops
+}
+
+/// Splits text into words and the whitespace between them.
+fn words(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut last_space = None;
+
+    for (i, c) in text.char_indices() {
+        let space = c.is_whitespace();
+        if last_space.is_some() && last_space != Some(space) {
+            tokens.push(&text[start..i]);
+            start = i;
+        }
+        last_space = Some(space);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+
+    tokens
+}
+
+/// Escapes characters Discord uses for formatting.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '*' | '_' | '~' | '`' | '|' | '\\' | '>') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Shows the changes between two texts word by word. Removed words are struck
+/// through and added words are in bold.
+pub fn inline_diff(old: &str, new: &str) -> String {
+    let old_words = words(old);
+    let new_words = words(new);
+    let ops = diff(&old_words, &new_words);
+
+    let mut text = String::new();
+    let mut index = 0;
+    while index < ops.len() {
+        let op = ops[index].0;
+        let mut run = String::new();
+        while index < ops.len() && ops[index].0 == op {
+            run.push_str(ops[index].1);
+            index += 1;
+        }
+
+        let marker = match op {
+            Op::Same => {
+                text.push_str(&escape(&run));
+                continue;
+            }
+            Op::Removed => "~~",
+            Op::Added => "**",
+        };
+
+        // Markers only work next to words, so whitespace around them is left out.
+        let trimmed = run.trim();
+        if trimmed.is_empty() {
+            if op == Op::Added {
+                text.push_str(&run);
+            }
+            continue;
+        }
+        let start = run.len() - run.trim_start().len();
+        let end = start + trimmed.len();
+        text.push_str(&run[..start]);
+        text.push_str(marker);
+        text.push_str(&escape(trimmed));
+        text.push_str(marker);
+        text.push_str(&run[end..]);
+    }
+
+    text
+}
+
+/// Checks whether two texts only differ in whitespace, which the inline diff
+/// can't show.
+pub fn whitespace_only(old: &str, new: &str) -> bool {
+    old != new && old.split_whitespace().eq(new.split_whitespace())
+}
+
+/// Shows the changes between two texts line by line, in the unified diff format.
+pub fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff(&old_lines, &new_lines);
+
+    let mut text = format!("--- {}\n+++ {}\n", old_name, new_name);
+
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, _))| *op != Op::Same)
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut index = 0;
+    while index < changed.len() {
+        // A hunk covers changes closer to each other than twice the context.
+        let first = changed[index];
+        let mut last = first;
+        while index + 1 < changed.len() && changed[index + 1] - last <= CONTEXT_LINES * 2 {
+            index += 1;
+            last = changed[index];
+        }
+        index += 1;
+
+        let start = first.saturating_sub(CONTEXT_LINES);
+        let end = (last + CONTEXT_LINES + 1).min(ops.len());
+
+        // How many lines come before the hunk, and how many it has, in both texts.
+        let old_start = ops[..start].iter().filter(|(o, _)| *o != Op::Added).count();
+        let new_start = ops[..start]
+            .iter()
+            .filter(|(o, _)| *o != Op::Removed)
+            .count();
+        let old_count = ops[start..end]
+            .iter()
+            .filter(|(o, _)| *o != Op::Added)
+            .count();
+        let new_count = ops[start..end]
+            .iter()
+            .filter(|(o, _)| *o != Op::Removed)
+            .count();
+
+        // Empty ranges are numbered after the line before them.
+        let line_number = |start: usize, count: usize| if count == 0 { start } else { start + 1 };
+        let _ = writeln!(
+            text,
+            "@@ -{},{} +{},{} @@",
+            line_number(old_start, old_count),
+            old_count,
+            line_number(new_start, new_count),
+            new_count
+        );
+        for (op, line) in &ops[start..end] {
+            let prefix = match op {
+                Op::Same => ' ',
+                Op::Removed => '-',
+                Op::Added => '+',
+            };
+            text.push(prefix);
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_insert() {
+        assert_eq!(
+            inline_diff("hello world", "hello big world"),
+            "hello **big** world"
+        );
+    }
+
+    #[test]
+    fn inline_delete() {
+        assert_eq!(
+            inline_diff("hello big world", "hello world"),
+            "hello ~~big~~ world"
+        );
+    }
+
+    #[test]
+    fn inline_escapes_markdown() {
+        assert_eq!(
+            inline_diff("a *b* `c`", "a *d* `c`"),
+            "a ~~\\*b\\*~~**\\*d\\*** \\`c\\`"
+        );
+    }
+
+    #[test]
+    fn whitespace_only_edits() {
+        assert!(whitespace_only("hello world", "hello  world"));
+        assert!(!whitespace_only("hello world", "hello world"));
+        assert!(!whitespace_only("hello world", "hello there"));
+    }
+
+    #[test]
+    fn unified_context() {
+        assert_eq!(
+            unified_diff("a\nb", "a\nb\nc", "before", "after"),
+            "--- before\n+++ after\n@@ -1,2 +1,3 @@\n a\n b\n+c\n"
+        );
+    }
+
+    #[test]
+    fn unified_pure_add() {
+        assert_eq!(
+            unified_diff("", "a\nb", "before", "after"),
+            "--- before\n+++ after\n@@ -0,0 +1,2 @@\n+a\n+b\n"
+        );
+    }
+
+    #[test]
+    fn unified_pure_delete() {
+        assert_eq!(
+            unified_diff("a\nb", "", "before", "after"),
+            "--- before\n+++ after\n@@ -1,2 +0,0 @@\n-a\n-b\n"
+        );
+    }
+}
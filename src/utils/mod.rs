@@ -2,6 +2,9 @@ pub mod checks;
 pub mod constants;
 pub mod converters;
 pub mod database;
+pub mod diff;
 pub mod formatting;
 pub mod message;
+pub mod storage;
 pub mod tos;
+pub mod transaction;
@@ -0,0 +1,179 @@
+//! Store files, like attachments of logged messages, on the local disk or in
+//! an S3-compatible bucket.
+//!
+//! The store is chosen with the `STORAGE` environment variable. See
+//! `.env.example` for the variables each store needs.
+
+use chrono::Utc;
+use hmac::{Hmac, Mac, NewMac};
+use reqwest::{Method, Url};
+use sha2::{Digest, Sha256};
+use std::{env, path::PathBuf};
+use tokio::fs;
+
+pub(crate) type StorageResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// Where files are stored.
+pub(crate) enum Storage {
+    /// A directory on the local disk.
+    Local(PathBuf),
+    /// A bucket of an S3-compatible service.
+    S3(S3Bucket),
+}
+
+pub(crate) struct S3Bucket {
+    client: reqwest::Client,
+    /// URL of the service, like `https://s3.eu-west-1.amazonaws.com`.
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+}
+
+impl Storage {
+    /// Creates the store set up in the environment.
+    ///
+    /// Files are stored in the `attachments` directory if nothing is set.
+    pub(crate) fn from_env() -> StorageResult<Self> {
+        match env::var("STORAGE").as_deref() {
+            Ok("s3") => {
+                let var = |name: &str| {
+                    env::var(name).map_err(|_| format!("`{}` must be set to use S3 storage.", name))
+                };
+
+                Ok(Self::S3(S3Bucket {
+                    client: reqwest::Client::new(),
+                    endpoint: Url::parse(&var("S3_ENDPOINT")?)?,
+                    bucket: var("S3_BUCKET")?,
+                    region: env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+                    access_key: var("S3_ACCESS_KEY")?,
+                    secret_key: var("S3_SECRET_KEY")?,
+                }))
+            }
+            Ok("local") | Err(_) => Ok(Self::Local(PathBuf::from(
+                env::var("STORAGE_PATH").unwrap_or_else(|_| "attachments".to_string()),
+            ))),
+            Ok(other) => Err(format!("`{}` isn't a valid storage.", other).into()),
+        }
+    }
+
+    /// Saves a file under `key`, replacing any file already there.
+    pub(crate) async fn put(&self, key: &str, data: Vec<u8>) -> StorageResult<()> {
+        match self {
+            Self::Local(dir) => {
+                let path = dir.join(key);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).await?;
+                }
+                fs::write(path, data).await?;
+            }
+            Self::S3(bucket) => {
+                bucket.request(Method::PUT, key, data).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the file saved under `key`.
+    pub(crate) async fn get(&self, key: &str) -> StorageResult<Vec<u8>> {
+        match self {
+            Self::Local(dir) => Ok(fs::read(dir.join(key)).await?),
+            Self::S3(bucket) => bucket.request(Method::GET, key, Vec::new()).await,
+        }
+    }
+
+    /// Deletes the file saved under `key`. Missing files are ignored.
+    pub(crate) async fn delete(&self, key: &str) -> StorageResult<()> {
+        match self {
+            Self::Local(dir) => match fs::remove_file(dir.join(key)).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            },
+            Self::S3(bucket) => bucket
+                .request(Method::DELETE, key, Vec::new())
+                .await
+                .map(|_| ()),
+        }
+    }
+}
+
+impl S3Bucket {
+    /// Sends a request signed with AWS Signature Version 4.
+    async fn request(&self, method: Method, key: &str, body: Vec<u8>) -> StorageResult<Vec<u8>> {
+        let path = format!(
+            "{}/{}/{}",
+            self.endpoint.path().trim_end_matches('/'),
+            self.bucket,
+            key
+        );
+        let mut url = self.endpoint.clone();
+        url.set_path(&path);
+
+        let host = match (url.host_str(), url.port()) {
+            (Some(h), Some(p)) => format!("{}:{}", h, p),
+            (Some(h), None) => h.to_string(),
+            _ => return Err("The S3 endpoint has no host.".into()),
+        };
+
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+            method.as_str(),
+            url.path(),
+            host,
+            payload_hash,
+            amz_date,
+            payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let mut signing_key = hmac(
+            format!("AWS4{}", self.secret_key).as_bytes(),
+            date.as_bytes(),
+        );
+        for part in [self.region.as_str(), "s3", "aws4_request"].iter() {
+            signing_key = hmac(&signing_key, part.as_bytes());
+        }
+        let signature = hex::encode(hmac(&signing_key, string_to_sign.as_bytes()));
+
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+            self.access_key, scope, signature
+        );
+
+        let res = self
+            .client
+            .request(method, url)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header("authorization", authorization)
+            .body(body)
+            .send()
+            .await?;
+
+        if res.status().is_success() {
+            Ok(res.bytes().await?.to_vec())
+        } else {
+            Err(format!("The S3 request failed with status {}.", res.status()).into())
+        }
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    // HMAC accepts keys of any length, so this can't fail.
+    let mut mac = Hmac::<Sha256>::new_varkey(key).unwrap();
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
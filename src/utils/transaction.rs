@@ -0,0 +1,117 @@
+//! Keep track of changes made by commands with many steps, so they can be
+//! undone if one of the steps fails.
+
+use serenity::{
+    model::prelude::{
+        ChannelId, GuildChannel, GuildId, PermissionOverwrite, PermissionOverwriteType, Role,
+        RoleId,
+    },
+    prelude::Context,
+};
+
+/// A change that can be undone.
+enum Change {
+    Channel(ChannelId, String),
+    Role(GuildId, RoleId, String),
+    Overwrite {
+        channel_id: ChannelId,
+        channel_name: String,
+        kind: PermissionOverwriteType,
+        previous: Option<PermissionOverwrite>,
+    },
+}
+
+/// Changes made by a command so far.
+#[derive(Default)]
+pub(crate) struct Transaction {
+    changes: Vec<Change>,
+}
+
+impl Transaction {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a channel or category that was created.
+    pub(crate) fn channel(&mut self, channel: &GuildChannel) {
+        self.changes
+            .push(Change::Channel(channel.id, channel.name.clone()));
+    }
+
+    /// Records a role that was created.
+    pub(crate) fn role(&mut self, role: &Role) {
+        self.changes
+            .push(Change::Role(role.guild_id, role.id, role.name.clone()));
+    }
+
+    /// Records the overwrite of `kind` a channel has before it is changed.
+    pub(crate) fn overwrite(&mut self, channel: &GuildChannel, kind: PermissionOverwriteType) {
+        let previous = channel
+            .permission_overwrites
+            .iter()
+            .find(|p| p.kind == kind)
+            .cloned();
+
+        self.changes.push(Change::Overwrite {
+            channel_id: channel.id,
+            channel_name: channel.name.clone(),
+            kind,
+            previous,
+        });
+    }
+
+    /// Undoes all changes, starting from the latest one.
+    ///
+    /// Returns a message telling what couldn't be undone, if anything.
+    pub(crate) async fn rollback(self, ctx: &Context) -> String {
+        let mut failed = Vec::new();
+
+        for change in self.changes.into_iter().rev() {
+            let res = match &change {
+                Change::Channel(id, _) => id.delete(&ctx.http).await.map(|_| ()),
+                Change::Role(guild_id, id, _) => guild_id.delete_role(&ctx.http, id.0).await,
+                Change::Overwrite {
+                    channel_id,
+                    kind,
+                    previous,
+                    ..
+                } => match previous {
+                    Some(p) => channel_id.create_permission(&ctx.http, p).await,
+                    None => channel_id.delete_permission(&ctx.http, *kind).await,
+                },
+            };
+
+            if res.is_err() {
+                failed.push(change.describe());
+            }
+        }
+
+        if failed.is_empty() {
+            "I undid all the changes made so far.".to_string()
+        } else {
+            format!(
+                "I couldn't undo these changes, please fix them yourself: {}.",
+                failed.join(", ")
+            )
+        }
+    }
+}
+
+impl Change {
+    fn describe(&self) -> String {
+        match self {
+            Self::Channel(id, name) => format!("`{}` ({})", name, id.0),
+            Self::Role(_, id, name) => format!("`@{}` ({})", name, id.0),
+            Self::Overwrite {
+                channel_name, kind, ..
+            } => {
+                let target = match kind {
+                    PermissionOverwriteType::Member(id) => format!("<@{}>", id.0),
+                    PermissionOverwriteType::Role(id) => format!("<@&{}>", id.0),
+                    _ => "someone".to_string(),
+                };
+                format!("permissions of {} in `{}`", target, channel_name)
+            }
+        }
+    }
+}
@@ -0,0 +1,220 @@
+// This module watches votes that are edited or deleted.
+//
+// Players may edit a vote after the fact, or delete an earlier vote to change
+// the count. When an edit or a delete in the voting channel of the current
+// cycle changes the vote of a message, hosts are alerted in the log channel
+// with the old and the new vote, and the change is recorded so `votecount`
+// can flag the voter. While vote changes are logged, messages in the voting
+// channel are archived, so changes to votes the cache has lost are noticed too.
+
+use crate::{
+    commands::{
+        setup::Cycle,
+        user::{canonical_vote, get_vote_from_message, Vote},
+    },
+    events::{get_log_channel, LogEvent, LogSettings},
+};
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serenity::{
+    model::prelude::{ChannelId, GuildId, MessageId, UserId},
+    prelude::Context,
+};
+use sqlx::{types::Json, PgPool};
+use std::collections::HashMap;
+use tracing::{error, instrument};
+
+static USER_MENTION_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<@!?(\d+)>").unwrap());
+
+struct VoteSettings {
+    host_role_id: Option<i64>,
+    cycle: Option<Json<Cycle>>,
+    anonymous: bool,
+}
+
+/// An edited or deleted message.
+#[derive(Debug)]
+pub(crate) struct VoteMessage<'a> {
+    pub guild_id: GuildId,
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    pub author_id: UserId,
+    pub author_tag: &'a str,
+}
+
+/// Finds the vote in the content of a message, writing mentioned users and
+/// aliases like `votecount` does.
+async fn vote_in(ctx: &Context, aliases: &HashMap<UserId, String>, content: &str) -> Option<Vote> {
+    let mentions: Vec<(String, u64)> = USER_MENTION_RE
+        .captures_iter(content)
+        .filter_map(|c| c[1].parse().ok().map(|id| (c[0].to_string(), id)))
+        .collect();
+
+    let mut text = content.to_string();
+    for (mention, id) in mentions {
+        if let Some(user) = ctx.cache.user(id).await {
+            text = text.replace(&mention, &user.name);
+        }
+    }
+
+    get_vote_from_message(text).map(|v| canonical_vote(aliases, v))
+}
+
+fn describe_vote(vote: &Option<Vote>) -> String {
+    match vote {
+        Some(Vote::Vtl(s)) => format!("VTL {}", s),
+        Some(Vote::UnVtl(s)) if s.is_empty() => "UnVTL".to_string(),
+        Some(Vote::UnVtl(s)) => format!("UnVTL {}", s),
+        Some(Vote::Vtnl) => "VTNL".to_string(),
+        None => "No vote".to_string(),
+    }
+}
+
+/// Alerts hosts if an edit or a delete changed a vote in the current voting
+/// channel.
+///
+/// `new_content` is `None` if the message was deleted.
+#[instrument(skip(ctx, pool))]
+pub(crate) async fn vote_change_handler(
+    ctx: &Context,
+    pool: &PgPool,
+    message: VoteMessage<'_>,
+    old_content: &str,
+    new_content: Option<&str>,
+) {
+    let res = sqlx::query_as_unchecked!(
+        VoteSettings,
+        "SELECT host_role_id, cycle, anonymous FROM config WHERE guild_id = $1;",
+        message.guild_id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await;
+
+    let settings = match res {
+        Ok(Some(s)) => s,
+        _ => return,
+    };
+
+    let votes = settings.cycle.as_ref().and_then(|c| c.0.votes);
+    if votes != Some(message.channel_id.0 as i64) {
+        return;
+    }
+
+    let aliases: HashMap<UserId, String> = if settings.anonymous {
+        match sqlx::query!(
+            "SELECT user_id, alias FROM aliases WHERE guild_id = $1;",
+            message.guild_id.0 as i64
+        )
+        .fetch_all(pool)
+        .await
+        {
+            Ok(a) => a
+                .into_iter()
+                .map(|a| (UserId(a.user_id as u64), a.alias))
+                .collect(),
+            Err(_) => HashMap::new(),
+        }
+    } else {
+        HashMap::new()
+    };
+
+    let old_vote = vote_in(ctx, &aliases, old_content).await;
+    let new_vote = match new_content {
+        Some(c) => vote_in(ctx, &aliases, c).await,
+        None => None,
+    };
+    if old_vote.is_none() || old_vote == new_vote {
+        return;
+    }
+
+    let deleted = new_content.is_none();
+    let old_desc = describe_vote(&old_vote);
+    let new_desc = if deleted {
+        "Deleted".to_string()
+    } else {
+        describe_vote(&new_vote)
+    };
+
+    let res = sqlx::query!(
+        "
+        INSERT INTO vote_changes(guild_id, channel_id, message_id, user_id, user_tag,
+        old_vote, new_vote, deleted) VALUES($1, $2, $3, $4, $5, $6, $7, $8);
+        ",
+        message.guild_id.0 as i64,
+        message.channel_id.0 as i64,
+        message.message_id.0 as i64,
+        message.author_id.0 as i64,
+        message.author_tag,
+        old_desc,
+        new_desc,
+        deleted
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(why) = res {
+        error!("Unable to record vote change: {}", why);
+    }
+
+    let log_settings = match sqlx::query_as!(
+        LogSettings,
+        "SELECT * FROM logging WHERE guild_id = $1;",
+        message.guild_id.0 as i64
+    )
+    .fetch_optional(pool)
+    .await
+    {
+        Ok(Some(s)) if s.logs(LogEvent::Votes) => s,
+        _ => return,
+    };
+
+    let log_channel =
+        match get_log_channel(ctx, pool, message.guild_id, &log_settings, LogEvent::Votes).await {
+            Ok(c) => c,
+            Err(_) => return,
+        };
+
+    let action = if deleted { "deleted" } else { "edited" };
+    let description = format!(
+        "<@{}> {} a vote in <#{}>.",
+        message.author_id.0, action, message.channel_id.0
+    );
+
+    let msg = log_channel
+        .send_message(&ctx.http, |m| {
+            if let Some(id) = settings.host_role_id {
+                m.content(format!("<@&{}>", id));
+                m.allowed_mentions(|am| am.roles(vec![id as u64]));
+            }
+            m.embed(|e| {
+                e.colour(0xFF0000);
+                e.description(description);
+                e.timestamp(Utc::now().to_rfc3339());
+                e.field("Old Vote", old_desc, true);
+                e.field("New Vote", new_desc, true);
+                e.footer(|f| {
+                    f.text(format!(
+                        "Author ID: {} | Message ID: {}",
+                        message.author_id.0, message.message_id.0
+                    ));
+
+                    f
+                });
+                e.author(|a| {
+                    a.name(format!("{} - Vote Changed", message.author_tag));
+
+                    a
+                });
+
+                e
+            });
+
+            m
+        })
+        .await;
+
+    if let Err(why) = msg {
+        error!("Failed to send vote change alert: {}", why);
+    }
+}